@@ -179,7 +179,7 @@ fn visit_ty(&mut self, ty: Ty<'tcx>, ty_context: TyContext) {
             | TyContext::LocalDecl { source_info: SourceInfo { span, .. }, .. } => {
                 span_bug!(span, "should not be visiting outside of the CFG: {:?}", ty_context);
             }
-            TyContext::Location(location) => {
+            TyContext::Location(location) | TyContext::ConstArg(location) => {
                 self.record_regions_live_at(ty, location);
             }
         }
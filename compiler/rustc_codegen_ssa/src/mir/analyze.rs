@@ -225,6 +225,7 @@ fn visit_local(&mut self, local: mir::Local, context: PlaceContext, location: Lo
             )
             | PlaceContext::NonMutatingUse(
                 NonMutatingUseContext::Inspect
+                | NonMutatingUseContext::CopyForDeref
                 | NonMutatingUseContext::SharedBorrow
                 | NonMutatingUseContext::FakeBorrow
                 | NonMutatingUseContext::AddressOf
@@ -0,0 +1,44 @@
+//! Collects the borrows created by a MIR body.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, BorrowKind, Location, Place, Rvalue};
+use crate::ty::Region;
+
+/// A borrow created by an `Rvalue::Ref`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BorrowSite<'tcx> {
+    /// The borrowed place.
+    pub place: Place<'tcx>,
+    pub kind: BorrowKind,
+    /// The region of the created reference.
+    pub region: Region<'tcx>,
+    /// The location of the statement creating the borrow.
+    pub location: Location,
+}
+
+/// Collects every `Rvalue::Ref` of a body as a [`BorrowSite`].
+///
+/// Raw borrows (`&raw const place`) don't create a reference, so they are not borrow sites.
+/// A nested borrow like `&&x` is built in several statements, each borrowing the result of
+/// the previous one, so it produces one borrow site per `&`, from the innermost outwards.
+pub struct BorrowSites<'tcx> {
+    pub sites: Vec<BorrowSite<'tcx>>,
+}
+
+impl<'tcx> BorrowSites<'tcx> {
+    /// Returns the borrow sites of `body`, in visiting order.
+    pub fn collect(body: &Body<'tcx>) -> Vec<BorrowSite<'tcx>> {
+        let mut visitor = BorrowSites { sites: Vec::new() };
+        visitor.visit_body(body);
+        visitor.sites
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for BorrowSites<'tcx> {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Ref(region, kind, place) = *rvalue {
+            self.sites.push(BorrowSite { place, kind, region, location });
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
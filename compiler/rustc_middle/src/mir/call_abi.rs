@@ -0,0 +1,41 @@
+//! Resolves the ABI of the calls of a MIR body.
+
+use rustc_span::source_map::Spanned;
+use rustc_target::spec::abi::Abi;
+
+use crate::mir::{traversal, Body, HasLocalDecls, Location, Operand, TerminatorKind};
+use crate::ty::{self, TyCtxt};
+
+/// Returns the ABI of a call to `func`, as given by the signature of its type.
+///
+/// Both function items and function pointers carry their ABI in their type, so this is `Some`
+/// for every well-formed callee, including calls through function pointers. It is `None` if
+/// the type of `func` is not a function type, which only happens in bodies with type errors.
+pub fn call_abi<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    local_decls: &impl HasLocalDecls<'tcx>,
+    func: &Operand<'tcx>,
+) -> Option<Abi> {
+    let ty = func.ty(local_decls, tcx);
+    match ty.kind() {
+        ty::FnDef(..) | ty::FnPtr(_) => Some(ty.fn_sig(tcx).abi()),
+        _ => None,
+    }
+}
+
+/// Calls `f` once for every `Call` terminator reachable from the start block of `body`, with
+/// the callee, the arguments, the ABI of the callee as given by [`call_abi`], and the location
+/// of the terminator.
+///
+/// Calls are visited in preorder. Tail calls are not `Call` terminators, and are not visited.
+pub fn for_each_call_abi<'a, 'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    mut f: impl FnMut(&'a Operand<'tcx>, &'a [Spanned<Operand<'tcx>>], Option<Abi>, Location),
+) {
+    for (block, data) in traversal::preorder(body) {
+        if let TerminatorKind::Call { func, args, .. } = &data.terminator().kind {
+            f(func, args, call_abi(tcx, body, func), body.terminator_loc(block));
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Extraction of call graph edges from a MIR body.
+
+use rustc_hir::def_id::DefId;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Location, Terminator, TerminatorKind};
+use crate::ty::TyCtxt;
+
+/// Collects a `(caller, callee)` edge for every `Call` and `TailCall` terminator in a body.
+///
+/// The callee is only resolved when the called operand is a constant function item. Calls
+/// through function pointers, closures held in locals, or any other dynamic value are
+/// recorded with a callee of `None`. In particular, no attempt is made to resolve calls
+/// through trait objects or to resolve trait methods to their implementations: the callee
+/// is the `DefId` of the trait method itself in that case.
+pub struct CallGraphEdges {
+    caller: DefId,
+    pub edges: Vec<(DefId, Option<DefId>)>,
+}
+
+impl CallGraphEdges {
+    pub fn new(caller: DefId) -> Self {
+        CallGraphEdges { caller, edges: Vec::new() }
+    }
+
+    /// Returns the call graph edges of `body`, using the body's `DefId` as the caller.
+    pub fn collect<'tcx>(body: &Body<'tcx>) -> Vec<(DefId, Option<DefId>)> {
+        let mut visitor = CallGraphEdges::new(body.source.def_id());
+        visitor.visit_body(body);
+        visitor.edges
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for CallGraphEdges {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        match &terminator.kind {
+            TerminatorKind::Call { func, .. } | TerminatorKind::TailCall { func, .. } => {
+                let callee = func.const_fn_def().map(|(def_id, _)| def_id);
+                self.edges.push((self.caller, callee));
+            }
+            _ => {}
+        }
+        self.super_terminator(terminator, location);
+    }
+}
+
+/// Collects the intrinsics called by a body, together with the location of each call.
+///
+/// Only calls whose callee is a constant function item that resolves to an intrinsic are
+/// found. Intrinsics called through function pointers, or from functions that are not
+/// inlined into the body, are not reported.
+pub struct CalledIntrinsics<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub calls: Vec<(DefId, Location)>,
+}
+
+impl<'tcx> CalledIntrinsics<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        CalledIntrinsics { tcx, calls: Vec::new() }
+    }
+
+    /// Returns the intrinsic calls of `body`.
+    pub fn collect(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Vec<(DefId, Location)> {
+        let mut visitor = CalledIntrinsics::new(tcx);
+        visitor.visit_body(body);
+        visitor.calls
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for CalledIntrinsics<'tcx> {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        match &terminator.kind {
+            TerminatorKind::Call { func, .. } | TerminatorKind::TailCall { func, .. } => {
+                if let Some((def_id, _)) = func.const_fn_def()
+                    && self.tcx.intrinsic(def_id).is_some()
+                {
+                    self.calls.push((def_id, location));
+                }
+            }
+            _ => {}
+        }
+        self.super_terminator(terminator, location);
+    }
+}
@@ -0,0 +1,46 @@
+//! Finds the fields of the capture struct of a closure or coroutine body that are used.
+
+use rustc_index::bit_set::GrowableBitSet;
+use rustc_target::abi::FieldIdx;
+
+use crate::mir::visit::{NonUseContext, PlaceContext, Visitor};
+use crate::mir::{Body, Location, Place, ProjectionElem};
+use crate::ty::CAPTURE_STRUCT_LOCAL;
+
+/// Collects the fields of `CAPTURE_STRUCT_LOCAL` that are accessed by a body, i.e. the upvars
+/// that the body uses, and for a coroutine-closure's coroutine, the arguments as well.
+///
+/// A field is used when a place starts with a projection to it, e.g. `_1.0` in a coroutine, or
+/// `(*_1).0` in a closure whose capture struct is passed by reference. Uses of the whole capture
+/// struct, like a move of `_1`, don't use any field in particular, and the mentions of upvars
+/// in debuginfo are not uses either. This is only meaningful for the bodies of closures and
+/// coroutines: in other bodies, `CAPTURE_STRUCT_LOCAL` is just the first argument.
+pub struct CaptureFieldUses {
+    fields: GrowableBitSet<FieldIdx>,
+}
+
+impl CaptureFieldUses {
+    /// Returns the fields of the capture struct that `body` uses.
+    pub fn collect<'tcx>(body: &Body<'tcx>) -> GrowableBitSet<FieldIdx> {
+        let mut visitor = CaptureFieldUses { fields: GrowableBitSet::new_empty() };
+        visitor.visit_body(body);
+        visitor.fields
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for CaptureFieldUses {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if place.local == CAPTURE_STRUCT_LOCAL
+            && !matches!(context, PlaceContext::NonUse(NonUseContext::VarDebugInfo))
+        {
+            let projection = match place.projection.split_first() {
+                Some((ProjectionElem::Deref, projection)) => projection,
+                _ => &place.projection[..],
+            };
+            if let Some(&ProjectionElem::Field(field, _)) = projection.first() {
+                self.fields.insert(field);
+            }
+        }
+        self.super_place(place, context, location);
+    }
+}
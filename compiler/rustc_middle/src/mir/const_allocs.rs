@@ -0,0 +1,52 @@
+//! Finds the allocations that the constants of a MIR body depend on.
+
+use rustc_data_structures::fx::FxIndexSet;
+
+use crate::mir::interpret::{AllocId, GlobalAlloc};
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Location};
+use crate::ty::TyCtxt;
+
+/// Collects the allocations that the evaluated constants of a body point to or are stored in,
+/// and, recursively, the allocations that those point to, e.g. to find the static data that a
+/// body needs.
+///
+/// Only the allocations of constant memory are walked: functions and vtables don't point to
+/// anything, and statics are collected but not walked, since that would require evaluating
+/// their initializers. Constants that are not evaluated yet, like the ones that depend on
+/// generic parameters, don't point to any allocation, so nothing is found for them.
+pub struct ConstAllocs<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    allocs: FxIndexSet<AllocId>,
+    /// The allocations that are collected but not walked yet.
+    stack: Vec<AllocId>,
+}
+
+impl<'tcx> ConstAllocs<'tcx> {
+    /// Returns the allocations that the constants of `body` depend on, in the order in which
+    /// they are found.
+    pub fn collect(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> FxIndexSet<AllocId> {
+        let mut visitor = ConstAllocs { tcx, allocs: FxIndexSet::default(), stack: Vec::new() };
+        visitor.visit_body(body);
+        while let Some(alloc_id) = visitor.stack.pop() {
+            if let GlobalAlloc::Memory(alloc) = tcx.global_alloc(alloc_id) {
+                for &(_, provenance) in alloc.inner().provenance().ptrs().iter() {
+                    visitor.insert(provenance.alloc_id());
+                }
+            }
+        }
+        visitor.allocs
+    }
+
+    fn insert(&mut self, alloc_id: AllocId) {
+        if self.allocs.insert(alloc_id) {
+            self.stack.push(alloc_id);
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for ConstAllocs<'tcx> {
+    fn visit_const_alloc_relocation(&mut self, alloc_id: AllocId, _location: Location) {
+        self.insert(alloc_id);
+    }
+}
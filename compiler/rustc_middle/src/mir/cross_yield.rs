@@ -0,0 +1,89 @@
+//! Computes the locals whose uses span a `Yield` terminator.
+
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+
+use crate::mir::visit::{MutatingUseContext, PlaceContext, Visitor};
+use crate::mir::{BasicBlock, Body, Local, Location, Terminator, TerminatorKind};
+
+/// Finds the locals that are used both before and after some `Yield` terminator of a
+/// coroutine body. Such locals may have to be saved in the coroutine state.
+///
+/// A use is "before" a yield if the yield is reachable from it, and "after" the yield if it
+/// is reachable from the yield's resume block. This is a reachability-based
+/// over-approximation of the liveness relation: a local that is reassigned after resuming
+/// before being read is still reported.
+///
+/// The resume argument of a yield is considered to be written at the start of the resume
+/// block, since that is when it gets its value.
+pub struct CrossYieldLocals {
+    /// For every yield, its location and its resume block.
+    yields: Vec<(Location, BasicBlock)>,
+    uses: IndexVec<Local, Vec<Location>>,
+}
+
+impl CrossYieldLocals {
+    /// Returns the set of locals of `body` whose uses span a yield.
+    pub fn compute<'tcx>(body: &Body<'tcx>) -> BitSet<Local> {
+        let mut visitor = CrossYieldLocals {
+            yields: Vec::new(),
+            uses: IndexVec::from_elem(Vec::new(), &body.local_decls),
+        };
+        visitor.visit_body(body);
+
+        let num_blocks = body.basic_blocks.len();
+        let mut result = BitSet::new_empty(body.local_decls.len());
+        for &(yield_location, resume) in &visitor.yields {
+            // Blocks from which the yield is reachable.
+            let mut before = BitSet::new_empty(num_blocks);
+            let mut stack = vec![yield_location.block];
+            while let Some(block) = stack.pop() {
+                if before.insert(block) {
+                    stack.extend(body.basic_blocks.predecessors()[block].iter().copied());
+                }
+            }
+
+            // Blocks reachable from the resume block.
+            let mut after = BitSet::new_empty(num_blocks);
+            let mut stack = vec![resume];
+            while let Some(block) = stack.pop() {
+                if after.insert(block) {
+                    stack.extend(body.basic_blocks[block].terminator().successors());
+                }
+            }
+
+            for (local, uses) in visitor.uses.iter_enumerated() {
+                let used_before = uses.iter().any(|location| before.contains(location.block));
+                let used_after = uses.iter().any(|location| after.contains(location.block));
+                if used_before && used_after {
+                    result.insert(local);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for CrossYieldLocals {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Yield { resume, .. } = terminator.kind {
+            self.yields.push((location, resume));
+        }
+        self.super_terminator(terminator, location);
+    }
+
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        if !context.is_use() {
+            return;
+        }
+        let location = match context {
+            PlaceContext::MutatingUse(MutatingUseContext::Yield) => {
+                // `visit_terminator` records the yield before its operands are visited.
+                let &(_, resume) = self.yields.last().unwrap();
+                Location { block: resume, statement_index: 0 }
+            }
+            _ => location,
+        };
+        self.uses[local].push(location);
+    }
+}
@@ -0,0 +1,170 @@
+//! Finds the assignments of a MIR body whose value is never read.
+
+use rustc_ast::InlineAsmOptions;
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+
+use crate::mir::visit::{MutatingUseContext, NonUseContext, PlaceContext, Visitor};
+use crate::mir::{BasicBlock, Body, Local, Location, Place, Rvalue, Terminator, TerminatorKind};
+
+/// What a statement or terminator does with a local, as far as dead stores are concerned.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Access {
+    /// The value of the local may be read.
+    Read,
+    /// The whole value of the local is overwritten or discarded without being read.
+    Kill,
+}
+
+/// Finds the assignments `_l = rvalue` whose value can't be read: on every path from the
+/// assignment, `_l` is overwritten, has its storage killed, or is not used at all before
+/// the end of the function.
+///
+/// This is conservative, so it only reports stores that are dead:
+/// - Only assignments to a whole local are reported. An assignment to a projection of a
+///   local, like `_1.0 = ..`, only writes part of it, and the rest may still be read.
+/// - Any use of a local other than a full overwrite or a storage marker counts as a read,
+///   including a partial write, a drop and a retag. A partial write doesn't end the
+///   liveness of the previous value either, since the rest of it may still be read.
+/// - The stores to locals that are borrowed anywhere in the body are never reported, since
+///   the value may be read through the reference.
+///
+/// The value of the rvalue may still have to be computed for its side effects, e.g. if it
+/// may panic, so removing a dead store is not always the same as removing the statement.
+pub struct DeadStores {
+    /// For every statement and terminator of every block, how it accesses each local.
+    accesses: IndexVec<BasicBlock, Vec<Vec<(Local, Access)>>>,
+    /// For every block, the locals that its terminator overwrites only when it returns, like
+    /// the destination of a `Call`, which isn't written if the call unwinds.
+    written_on_return: IndexVec<BasicBlock, Vec<Local>>,
+    stores: Vec<(Local, Location)>,
+    borrowed: BitSet<Local>,
+}
+
+impl DeadStores {
+    /// Returns the locations of the dead stores of `body`, in visiting order.
+    pub fn compute<'tcx>(body: &Body<'tcx>) -> Vec<Location> {
+        let visitor = DeadStores::new(body);
+        visitor
+            .stores
+            .iter()
+            .filter(|&&(local, location)| {
+                !visitor.borrowed.contains(local) && !visitor.is_read_after(body, local, location)
+            })
+            .map(|&(_, location)| location)
+            .collect()
+    }
+
+    /// Records the accesses to the locals of `body`.
+    pub(crate) fn new(body: &Body<'_>) -> DeadStores {
+        let accesses = body
+            .basic_blocks
+            .iter()
+            .map(|data| vec![Vec::new(); data.statements.len() + 1])
+            .collect();
+        let mut visitor = DeadStores {
+            accesses,
+            written_on_return: IndexVec::from_elem(Vec::new(), &body.basic_blocks),
+            stores: Vec::new(),
+            borrowed: BitSet::new_empty(body.local_decls.len()),
+        };
+        visitor.visit_body(body);
+        visitor
+    }
+
+    /// Returns whether `local` may be read after `location` before it is killed.
+    pub(crate) fn is_read_after(&self, body: &Body<'_>, local: Local, location: Location) -> bool {
+        // Returns whether the rest of `block`, starting at `start`, reads `local`, or `None` if
+        // it neither reads nor kills it.
+        let scan = |block: BasicBlock, start: usize| {
+            self.accesses[block][start..].iter().find_map(|accesses| {
+                // Within a statement, reads happen before writes, e.g. in `_1 = Add(_1, _2)`.
+                if accesses.contains(&(local, Access::Read)) {
+                    Some(true)
+                } else if accesses.contains(&(local, Access::Kill)) {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+        };
+
+        let successors = |block: BasicBlock| {
+            let terminator = body.basic_blocks[block].terminator();
+            let written = self.written_on_return[block].contains(&local);
+            terminator
+                .successors()
+                .filter(move |&successor| !(written && returns_to(terminator, successor)))
+        };
+        let mut stack: Vec<BasicBlock> = match scan(location.block, location.statement_index + 1) {
+            Some(read) => return read,
+            None => successors(location.block).collect(),
+        };
+        // The block of the store can be visited again, from its start, through a back-edge.
+        let mut visited = BitSet::new_empty(body.basic_blocks.len());
+        while let Some(block) = stack.pop() {
+            if !visited.insert(block) {
+                continue;
+            }
+            match scan(block, 0) {
+                Some(true) => return true,
+                Some(false) => {}
+                None => stack.extend(successors(block)),
+            }
+        }
+        false
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for DeadStores {
+    fn visit_assign(&mut self, place: &Place<'tcx>, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Some(local) = place.as_local() {
+            self.stores.push((local, location));
+        }
+        self.super_assign(place, rvalue, location);
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if (context.is_borrow() || context.is_address_of()) && !place.is_indirect() {
+            self.borrowed.insert(place.local);
+        }
+        self.super_place(place, context, location);
+    }
+
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        let access = match context {
+            // Only full overwrites reach `visit_local` with these contexts: writes to a
+            // projection of the local are visited as `MutatingUseContext::Projection`.
+            PlaceContext::MutatingUse(MutatingUseContext::Store | MutatingUseContext::Deinit)
+            | PlaceContext::NonUse(NonUseContext::StorageLive | NonUseContext::StorageDead) => {
+                Access::Kill
+            }
+            // These are written by a terminator, but only on the edges where it returns, see
+            // `returns_to`, so they don't end the liveness of the previous value on unwind.
+            PlaceContext::MutatingUse(
+                MutatingUseContext::Call
+                | MutatingUseContext::AsmOutput
+                | MutatingUseContext::Yield,
+            ) => {
+                self.written_on_return[location.block].push(local);
+                return;
+            }
+            PlaceContext::NonUse(_) => return,
+            _ => Access::Read,
+        };
+        self.accesses[location.block][location.statement_index].push((local, access));
+    }
+}
+
+/// Returns whether `successor` is reached when `terminator` returns, i.e. once it has written
+/// its destination, resume argument or outputs.
+fn returns_to(terminator: &Terminator<'_>, successor: BasicBlock) -> bool {
+    match &terminator.kind {
+        TerminatorKind::Call { target, .. } => *target == Some(successor),
+        TerminatorKind::Yield { resume, .. } => *resume == successor,
+        TerminatorKind::InlineAsm { options, targets, .. } => {
+            !options.contains(InlineAsmOptions::NORETURN) && targets.first() == Some(&successor)
+        }
+        _ => false,
+    }
+}
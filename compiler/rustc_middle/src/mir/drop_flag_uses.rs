@@ -0,0 +1,51 @@
+//! Finds the drop flags of a MIR body and their uses.
+
+use rustc_index::bit_set::BitSet;
+
+use crate::mir::visit::{PlaceContext, Visitor};
+use crate::mir::{Body, Local, LocalInfo, Location};
+
+/// Collects the uses of the drop flags of a body, e.g. to find the flags that are never read.
+///
+/// Drop flags are the boolean locals that the `ElaborateDrops` pass creates to track whether a
+/// place must be dropped, and they are the locals whose `LocalInfo` is `DropFlag`. They are
+/// written by assignments of constants and read by the `SwitchInt`s that guard the drops, so a
+/// flag without any non-mutating use is dead. Bodies loaded from other crates, including the
+/// ones inlined from other crates, don't record the origin of their locals, so their flags are
+/// not found.
+pub struct DropFlagUses {
+    flags: BitSet<Local>,
+    uses: Vec<(Local, PlaceContext, Location)>,
+}
+
+impl DropFlagUses {
+    /// Returns the drop flags of `body`, and every use of them with its context, in the order
+    /// of the blocks.
+    pub fn collect<'tcx>(
+        body: &Body<'tcx>,
+    ) -> (BitSet<Local>, Vec<(Local, PlaceContext, Location)>) {
+        let mut visitor =
+            DropFlagUses { flags: BitSet::new_empty(body.local_decls.len()), uses: Vec::new() };
+        for (local, local_decl) in body.local_decls.iter_enumerated() {
+            visitor.visit_local_decl(local, local_decl);
+        }
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            visitor.visit_basic_block_data(block, data);
+        }
+        (visitor.flags, visitor.uses)
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for DropFlagUses {
+    fn visit_local_info(&mut self, local: Local, local_info: &LocalInfo<'tcx>) {
+        if let LocalInfo::DropFlag = local_info {
+            self.flags.insert(local);
+        }
+    }
+
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        if self.flags.contains(local) {
+            self.uses.push((local, context, location));
+        }
+    }
+}
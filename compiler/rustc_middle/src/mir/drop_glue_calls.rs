@@ -0,0 +1,82 @@
+//! Recognizes the calls of a MIR body that run drop glue.
+
+use rustc_hir::LangItem;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, HasLocalDecls, Location, Operand, Terminator, TerminatorKind};
+use crate::ty::{self, Ty, TyCtxt};
+
+/// What a `Call` terminator calls, as far as dropping is concerned, see [`drop_glue_call`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DropGlueCall<'tcx> {
+    /// A call to `drop_in_place::<T>`, with `T`, which runs the whole drop glue of the pointee
+    /// of its argument, like a `Drop` terminator of that place.
+    DropInPlace(Ty<'tcx>),
+    /// A call to `<T as Drop>::drop`, with `T`, which only runs the `Drop` impl of `T` and not
+    /// the drop glue of its fields. Such calls only appear in drop glue itself.
+    DropImpl(Ty<'tcx>),
+    /// A call to any other function.
+    NotDropGlue,
+    /// A call through a function pointer, which may or may not point to drop glue, e.g. the
+    /// drop function of a vtable.
+    Unknown,
+}
+
+/// Tells whether a call to `func` runs drop glue.
+///
+/// The callee is resolved from the type of `func` alone: a function item is drop glue if it
+/// is the `drop_in_place` lang item or the `drop` method of the `Drop` trait, whatever its
+/// generic arguments are, so this works in generic bodies too. Function pointers can't be
+/// resolved statically, and neither can callees whose type is not a function type, which
+/// only happens in bodies with type errors, so both are `Unknown`.
+pub fn drop_glue_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    local_decls: &impl HasLocalDecls<'tcx>,
+    func: &Operand<'tcx>,
+) -> DropGlueCall<'tcx> {
+    match *func.ty(local_decls, tcx).kind() {
+        ty::FnDef(def_id, args) if tcx.is_lang_item(def_id, LangItem::DropInPlace) => {
+            DropGlueCall::DropInPlace(args.type_at(0))
+        }
+        ty::FnDef(def_id, args)
+            if tcx
+                .trait_of_item(def_id)
+                .is_some_and(|trait_def_id| tcx.is_lang_item(trait_def_id, LangItem::Drop)) =>
+        {
+            DropGlueCall::DropImpl(args.type_at(0))
+        }
+        ty::FnDef(..) => DropGlueCall::NotDropGlue,
+        _ => DropGlueCall::Unknown,
+    }
+}
+
+/// Collects the `Call` terminators of a body that are not known to be ordinary calls, so
+/// that drop-tracking passes can treat them like `Drop` terminators.
+pub struct DropGlueCalls<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    calls: Vec<(Location, DropGlueCall<'tcx>)>,
+}
+
+impl<'a, 'tcx> DropGlueCalls<'a, 'tcx> {
+    /// Returns the location of every call of `body` that runs drop glue or may run it, i.e.
+    /// every call whose [`drop_glue_call`] is not `NotDropGlue`, in the order of the blocks.
+    pub fn collect(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>) -> Vec<(Location, DropGlueCall<'tcx>)> {
+        let mut visitor = DropGlueCalls { tcx, body, calls: Vec::new() };
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            visitor.visit_terminator(data.terminator(), body.terminator_loc(block));
+        }
+        visitor.calls
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for DropGlueCalls<'_, 'tcx> {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Call { func, .. } = &terminator.kind {
+            match drop_glue_call(self.tcx, self.body, func) {
+                DropGlueCall::NotDropGlue => {}
+                call => self.calls.push((location, call)),
+            }
+        }
+    }
+}
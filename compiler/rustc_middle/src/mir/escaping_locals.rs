@@ -0,0 +1,88 @@
+//! Finds the locals whose address is passed to a call.
+
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Local, Location, Operand, Place, Rvalue, Terminator, TerminatorKind};
+
+/// Collects the locals that are borrowed, or whose address is taken, and then passed to a call,
+/// so that the callee may keep a pointer to them.
+///
+/// A reference passed to a call is traced back to the local it points to through the
+/// assignments of whole locals: a `Ref` or `AddressOf` of a place that is not behind a pointer,
+/// and the copies, moves and casts of locals, e.g. in `_2 = &_1; _3 = copy _2; f(move _3)`.
+/// This is flow-insensitive: a local that is assigned several times is assumed to hold any of
+/// the values assigned to it, wherever it is passed.
+///
+/// This is not a complete escape analysis. It misses the addresses that are passed through
+/// anything else than a local, like a field of an aggregate or the result of a call, and the
+/// ones that escape without a call, e.g. by being returned or written behind a pointer.
+pub struct EscapingLocals {
+    /// For every local, the locals that it may hold the address of.
+    addresses: IndexVec<Local, Vec<Local>>,
+    /// For every local, the locals that it may be a copy of.
+    copies: IndexVec<Local, Vec<Local>>,
+    /// The locals that are passed to calls.
+    arguments: Vec<Local>,
+}
+
+impl EscapingLocals {
+    /// Returns the locals of `body` whose address may be passed to a call.
+    pub fn collect<'tcx>(body: &Body<'tcx>) -> BitSet<Local> {
+        let mut visitor = EscapingLocals {
+            addresses: IndexVec::from_elem(Vec::new(), &body.local_decls),
+            copies: IndexVec::from_elem(Vec::new(), &body.local_decls),
+            arguments: Vec::new(),
+        };
+        visitor.visit_body(body);
+
+        let mut escaping = BitSet::new_empty(body.local_decls.len());
+        let mut visited = BitSet::new_empty(body.local_decls.len());
+        let mut stack = std::mem::take(&mut visitor.arguments);
+        while let Some(local) = stack.pop() {
+            if visited.insert(local) {
+                for &address in &visitor.addresses[local] {
+                    escaping.insert(address);
+                }
+                stack.extend_from_slice(&visitor.copies[local]);
+            }
+        }
+        escaping
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for EscapingLocals {
+    fn visit_assign(&mut self, place: &Place<'tcx>, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Some(local) = place.as_local() {
+            match rvalue {
+                Rvalue::Ref(_, _, borrowed) | Rvalue::AddressOf(_, borrowed)
+                    if !borrowed.is_indirect() =>
+                {
+                    self.addresses[local].push(borrowed.local)
+                }
+                Rvalue::Use(Operand::Copy(source) | Operand::Move(source))
+                | Rvalue::Cast(_, Operand::Copy(source) | Operand::Move(source), _) => {
+                    if let Some(source) = source.as_local() {
+                        self.copies[local].push(source);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.super_assign(place, rvalue, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Call { args, .. } | TerminatorKind::TailCall { args, .. } =
+            &terminator.kind
+        {
+            for arg in args.iter() {
+                if let Some(local) = arg.node.place().and_then(|place| place.as_local()) {
+                    self.arguments.push(local);
+                }
+            }
+        }
+        self.super_terminator(terminator, location);
+    }
+}
@@ -0,0 +1,63 @@
+//! Collects the free regions mentioned by a MIR body.
+
+use rustc_data_structures::fx::FxIndexMap;
+
+use crate::mir::visit::{TyContext, Visitor};
+use crate::mir::{Body, Location};
+use crate::ty::{self, GenericArgsRef, Region, Ty, TyCtxt};
+
+/// Where a free region was found by [`FreeRegions`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RegionSite {
+    /// In a type, in the given context.
+    Ty(TyContext),
+    /// In the generic arguments of an aggregate or of an inlined callee, at the given location.
+    Args(Location),
+    /// As the region of a borrow, at the given location.
+    Borrow(Location),
+}
+
+/// Collects every distinct free region of a body, with all the places where it appears.
+///
+/// Regions bound by a binder, like the ones of `for<'a> fn(&'a u8)`, and erased regions are
+/// not collected. Once the regions of a body have been erased, e.g. with
+/// `TyCtxt::erase_regions`, this is therefore empty: it is meant to be used before erasure,
+/// e.g. to see which regions of the signature and of the user type annotations the built MIR
+/// mentions, or to check that no region escaped erasure.
+pub struct FreeRegions<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub regions: FxIndexMap<Region<'tcx>, Vec<RegionSite>>,
+}
+
+impl<'tcx> FreeRegions<'tcx> {
+    /// Returns the free regions of `body`, in visiting order, with their sites.
+    pub fn collect(
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+    ) -> FxIndexMap<Region<'tcx>, Vec<RegionSite>> {
+        let mut visitor = FreeRegions { tcx, regions: FxIndexMap::default() };
+        visitor.visit_body(body);
+        visitor.regions
+    }
+
+    fn record(&mut self, region: Region<'tcx>, site: RegionSite) {
+        if !region.is_erased() && !matches!(*region, ty::ReBound(..)) {
+            self.regions.entry(region).or_default().push(site);
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for FreeRegions<'tcx> {
+    fn visit_ty(&mut self, ty: Ty<'tcx>, context: TyContext) {
+        self.tcx.for_each_free_region(&ty, |region| self.record(region, RegionSite::Ty(context)));
+    }
+
+    fn visit_args(&mut self, args: &GenericArgsRef<'tcx>, location: Location) {
+        self.tcx
+            .for_each_free_region(args, |region| self.record(region, RegionSite::Args(location)));
+    }
+
+    fn visit_region(&mut self, region: Region<'tcx>, location: Location) {
+        self.record(region, RegionSite::Borrow(location));
+    }
+}
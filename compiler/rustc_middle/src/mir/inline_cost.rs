@@ -0,0 +1,112 @@
+//! Estimates the cost of the blocks of a MIR body as a weighted count of their statements and
+//! terminators.
+
+use rustc_index::IndexVec;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{
+    BasicBlock, Body, Location, Operand, Statement, StatementKind, Terminator, TerminatorKind,
+};
+
+/// The weights used by [`InlineCost`].
+#[derive(Copy, Clone, Debug)]
+pub struct InlineCostWeights {
+    /// The cost of each statement that does something at runtime.
+    pub statement: usize,
+    /// The cost of each terminator other than a call.
+    pub terminator: usize,
+    /// The cost of each call, tail call or inline assembly terminator.
+    pub call: usize,
+    /// The cost of each operand, in statements and terminators alike.
+    pub operand: usize,
+}
+
+impl Default for InlineCostWeights {
+    /// Weights in the same range as the ones the MIR inliner uses.
+    fn default() -> Self {
+        InlineCostWeights { statement: 5, terminator: 5, call: 25, operand: 1 }
+    }
+}
+
+/// Computes a cheap estimate of the cost of a body, for heuristics like inlining.
+///
+/// The cost is the sum of the weights of the statements, terminators and operands of the
+/// body, including its cleanup blocks. Statements that don't do anything at runtime, like
+/// storage markers, `Nop`s and the statements only used by borrowck or coverage, are free.
+/// The cost doesn't depend on types, so e.g. a `Drop` of a type without drop glue costs as
+/// much as any other terminator.
+pub struct InlineCost {
+    pub weights: InlineCostWeights,
+    /// The cost of the statements and of the terminator of each block, operands included.
+    costs: IndexVec<BasicBlock, (usize, usize)>,
+    /// Whether the operands being visited belong to a terminator.
+    in_terminator: bool,
+}
+
+impl InlineCost {
+    /// Returns the cost of the statements and of the terminator of each block of `body`, with
+    /// the given weights. The operands are counted with the statement or terminator that uses
+    /// them.
+    pub fn compute<'tcx>(
+        body: &Body<'tcx>,
+        weights: InlineCostWeights,
+    ) -> IndexVec<BasicBlock, (usize, usize)> {
+        let costs = IndexVec::from_elem((0, 0), &body.basic_blocks);
+        let mut visitor = InlineCost { weights, costs, in_terminator: false };
+        visitor.visit_body(body);
+        visitor.costs
+    }
+
+    /// Returns the cost of the whole `body`, the sum of the costs of its blocks.
+    pub fn total<'tcx>(body: &Body<'tcx>, weights: InlineCostWeights) -> usize {
+        InlineCost::compute(body, weights)
+            .iter()
+            .map(|&(statements, terminator)| statements + terminator)
+            .sum()
+    }
+
+    fn cost_mut(&mut self, block: BasicBlock) -> &mut usize {
+        let (statements, terminator) = &mut self.costs[block];
+        if self.in_terminator {
+            terminator
+        } else {
+            statements
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for InlineCost {
+    fn visit_statement(&mut self, statement: &Statement<'tcx>, location: Location) {
+        match statement.kind {
+            StatementKind::StorageLive(_)
+            | StatementKind::StorageDead(_)
+            | StatementKind::Nop
+            | StatementKind::FakeRead(_)
+            | StatementKind::AscribeUserType(..)
+            | StatementKind::PlaceMention(_)
+            | StatementKind::Coverage(_)
+            | StatementKind::ConstEvalCounter => {}
+            _ => {
+                self.costs[location.block].0 += self.weights.statement;
+                self.super_statement(statement, location);
+            }
+        }
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        self.costs[location.block].1 += match terminator.kind {
+            TerminatorKind::Call { .. }
+            | TerminatorKind::TailCall { .. }
+            | TerminatorKind::InlineAsm { .. } => self.weights.call,
+            _ => self.weights.terminator,
+        };
+        self.in_terminator = true;
+        self.super_terminator(terminator, location);
+        self.in_terminator = false;
+    }
+
+    fn visit_operand(&mut self, operand: &Operand<'tcx>, location: Location) {
+        *self.cost_mut(location.block) += self.weights.operand;
+        self.super_operand(operand, location);
+    }
+}
@@ -0,0 +1,51 @@
+//! Instantiating the generic parameters of a MIR body with a `MutVisitor`.
+
+use crate::mir::visit::{MutVisitor, TyContext};
+use crate::mir::{ConstOperand, Location};
+use crate::ty::{self, GenericArgsRef, Ty, TyCtxt};
+
+/// Instantiates every type, region and constant of a body with `args`.
+///
+/// This is the visitor equivalent of folding a body with `EarlyBinder::instantiate`: each
+/// `Ty`, `Region`, `Const` and `GenericArgsRef` reached by the visitor is instantiated exactly
+/// once. Unlike `Instance::instantiate_mir_and_normalize_erasing_regions`, this neither
+/// normalizes nor erases regions, so callers that need either have to do it themselves.
+pub struct SubstApplier<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub args: GenericArgsRef<'tcx>,
+}
+
+impl<'tcx> SubstApplier<'tcx> {
+    fn instantiate<T: ty::TypeFoldable<TyCtxt<'tcx>>>(&self, value: T) -> T {
+        ty::EarlyBinder::bind(value).instantiate(self.tcx, self.args)
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for SubstApplier<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>, _: TyContext) {
+        *ty = self.instantiate(*ty);
+    }
+
+    fn visit_region(&mut self, region: &mut ty::Region<'tcx>, _: Location) {
+        *region = self.instantiate(*region);
+    }
+
+    fn visit_ty_const(&mut self, ct: &mut ty::Const<'tcx>, _: Location) {
+        *ct = self.instantiate(*ct);
+    }
+
+    fn visit_args(&mut self, args: &mut GenericArgsRef<'tcx>, _: Location) {
+        *args = self.instantiate(*args);
+    }
+
+    fn visit_const_operand(&mut self, constant: &mut ConstOperand<'tcx>, _: Location) {
+        // The default walk only reaches the type of unevaluated constants, not their generic
+        // arguments, so instantiate the whole constant at once instead.
+        constant.const_ = self.instantiate(constant.const_);
+        self.visit_span(&mut constant.span);
+    }
+}
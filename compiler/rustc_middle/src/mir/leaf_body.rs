@@ -0,0 +1,40 @@
+//! Checks whether a MIR body calls other functions.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Location, Terminator, TerminatorKind};
+
+/// Looks for the first call of a body, see [`is_leaf_body`].
+struct CallFinder {
+    include_drops: bool,
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for CallFinder {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, _location: Location) {
+        self.found = match terminator.kind {
+            TerminatorKind::Call { .. } | TerminatorKind::TailCall { .. } => true,
+            TerminatorKind::Drop { .. } => self.include_drops,
+            _ => false,
+        };
+    }
+}
+
+/// Returns whether `body` makes no calls, i.e. has no `Call` or `TailCall` terminator. The
+/// check stops at the first call found.
+///
+/// A `Drop` terminator calls the drop glue of the dropped type, which may or may not do
+/// anything: it only counts as a call if `include_drops` is true. Since types aren't looked
+/// at, a drop of a type without drop glue is still a call in that case. Intrinsics are called
+/// like other functions, so they count as calls, and so do the calls in cleanup blocks. Inline
+/// assembly is not a call, even though it may call functions itself.
+pub fn is_leaf_body(body: &Body<'_>, include_drops: bool) -> bool {
+    let mut finder = CallFinder { include_drops, found: false };
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        let location = body.terminator_loc(block);
+        finder.visit_terminator(data.terminator(), location);
+        if finder.found {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,43 @@
+//! Counts the basic blocks in which each local is used.
+
+use rustc_index::bit_set::SparseBitMatrix;
+use rustc_index::IndexVec;
+
+use crate::mir::visit::{NonUseContext, PlaceContext, Visitor};
+use crate::mir::{BasicBlock, Body, Local, Location};
+
+/// Records the set of blocks that mention each local, as a cheap estimate of how long the local
+/// has to stay in a register.
+///
+/// Every mention of a local in a statement or a terminator counts, whether it reads it, writes
+/// it, or only uses it as an index or the base of a projection. Storage markers count too,
+/// unless `include_storage` is false. Debuginfo doesn't belong to a block, so it never counts.
+pub struct LocalBlockSpread {
+    include_storage: bool,
+    blocks: SparseBitMatrix<Local, BasicBlock>,
+}
+
+impl LocalBlockSpread {
+    /// Returns, for every local of `body`, the number of distinct blocks that mention it.
+    pub fn compute<'tcx>(body: &Body<'tcx>, include_storage: bool) -> IndexVec<Local, usize> {
+        let blocks = SparseBitMatrix::new(body.basic_blocks.len());
+        let mut visitor = LocalBlockSpread { include_storage, blocks };
+        visitor.visit_body(body);
+        body.local_decls.indices().map(|local| visitor.blocks.iter(local).count()).collect()
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for LocalBlockSpread {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        match context {
+            PlaceContext::NonUse(NonUseContext::VarDebugInfo) => return,
+            PlaceContext::NonUse(NonUseContext::StorageLive | NonUseContext::StorageDead)
+                if !self.include_storage =>
+            {
+                return;
+            }
+            _ => {}
+        }
+        self.blocks.insert(local, location.block);
+    }
+}
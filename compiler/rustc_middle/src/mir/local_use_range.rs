@@ -0,0 +1,42 @@
+//! Approximates the live range of each local by its first and last use.
+
+use rustc_index::IndexVec;
+
+use crate::mir::visit::{PlaceContext, Visitor};
+use crate::mir::{Body, Local, Location};
+
+/// Records, for every local, the smallest and the largest location at which it is used.
+///
+/// Locations are compared by block index, then by statement index, which is *not* the
+/// order in which they execute: a use in a loop header can come after a use in the loop
+/// body, and the range of a local used in two unrelated branches covers every block
+/// numbered between them. The range is therefore only a cheap approximation of liveness,
+/// suitable for heuristics but not for correctness.
+///
+/// Storage markers and debuginfo are not uses, see [`PlaceContext::is_use`].
+pub struct LocalUseRange {
+    ranges: IndexVec<Local, Option<(Location, Location)>>,
+}
+
+impl LocalUseRange {
+    /// Returns the `(first, last)` use location of every local of `body`, or `None` for
+    /// the locals that are never used.
+    pub fn compute<'tcx>(body: &Body<'tcx>) -> IndexVec<Local, Option<(Location, Location)>> {
+        let mut visitor = LocalUseRange { ranges: IndexVec::from_elem(None, &body.local_decls) };
+        visitor.visit_body(body);
+        visitor.ranges
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for LocalUseRange {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        if !context.is_use() {
+            return;
+        }
+        let range = &mut self.ranges[local];
+        *range = match *range {
+            None => Some((location, location)),
+            Some((first, last)) => Some((first.min(location), last.max(location))),
+        };
+    }
+}
@@ -0,0 +1,37 @@
+//! Finds the locals of a MIR body that have a given origin.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Local, LocalInfo};
+
+/// Collects the locals whose `LocalInfo` satisfies a predicate, e.g. to find the temporaries
+/// that a pass introduced when debugging MIR optimizations.
+///
+/// Only some origins are recorded: `LocalInfo::DerefTemp` marks the temporaries introduced by
+/// the `Derefer` pass, while most temporaries introduced by other passes are
+/// `LocalInfo::Boring`, like the ones of MIR building without a more specific origin. Bodies
+/// loaded from other crates don't record the origin of their locals, so nothing is found in
+/// them.
+pub struct LocalsByOrigin<F> {
+    is_origin: F,
+    locals: Vec<Local>,
+}
+
+impl<'tcx, F: FnMut(&LocalInfo<'tcx>) -> bool> LocalsByOrigin<F> {
+    /// Returns the locals of `body` whose origin satisfies `is_origin`, in increasing order,
+    /// e.g. `|info| matches!(info, LocalInfo::DerefTemp)`.
+    pub fn collect(body: &Body<'tcx>, is_origin: F) -> Vec<Local> {
+        let mut visitor = LocalsByOrigin { is_origin, locals: Vec::new() };
+        for (local, local_decl) in body.local_decls.iter_enumerated() {
+            visitor.visit_local_decl(local, local_decl);
+        }
+        visitor.locals
+    }
+}
+
+impl<'tcx, F: FnMut(&LocalInfo<'tcx>) -> bool> Visitor<'tcx> for LocalsByOrigin<F> {
+    fn visit_local_info(&mut self, local: Local, local_info: &LocalInfo<'tcx>) {
+        if (self.is_origin)(local_info) {
+            self.locals.push(local);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Keeping `Location`s up to date while statements are inserted into or removed from a body.
+//!
+//! A `Location` is just a block and an index into that block's statements, so any pass that
+//! inserts or removes statements invalidates the locations computed before the edit. Passes
+//! that want downstream analyses to keep working with pre-edit locations can record their
+//! edits in a [`LocationMap`] and then translate stale locations with [`renumber_locations`].
+
+use rustc_data_structures::fx::FxHashMap;
+
+use crate::mir::{BasicBlock, Body, Location};
+
+/// Maps the locations of a body before a series of statement edits to the locations after
+/// those edits.
+///
+/// Edits are recorded in terms of the *current* statement indices, i.e. each edit is
+/// expressed relative to the body with all previously recorded edits already applied. This
+/// is the natural order when recording edits while performing them.
+#[derive(Clone, Debug)]
+pub struct LocationMap {
+    /// The number of statements of each block before any edit, including the terminator.
+    original_len: FxHashMap<BasicBlock, usize>,
+    /// For each edited block, the current index of every original statement (and of the
+    /// terminator as the last entry), or `None` if that statement was removed.
+    blocks: FxHashMap<BasicBlock, Vec<Option<usize>>>,
+}
+
+impl LocationMap {
+    pub fn new(body: &Body<'_>) -> Self {
+        let original_len = body
+            .basic_blocks
+            .iter_enumerated()
+            .map(|(bb, data)| (bb, data.statements.len() + 1))
+            .collect();
+        LocationMap { original_len, blocks: FxHashMap::default() }
+    }
+
+    fn block_mut(&mut self, block: BasicBlock) -> &mut Vec<Option<usize>> {
+        let len = self.original_len[&block];
+        self.blocks.entry(block).or_insert_with(|| (0..len).map(Some).collect())
+    }
+
+    /// Records that a statement was inserted at `location`, shifting the statement that was
+    /// previously there (and every following one) down by one.
+    pub fn record_insert(&mut self, location: Location) {
+        for index in self.block_mut(location.block).iter_mut().flatten() {
+            if *index >= location.statement_index {
+                *index += 1;
+            }
+        }
+    }
+
+    /// Records that the statement at `location` was removed.
+    ///
+    /// Removing the terminator is not supported, since a block always has one.
+    pub fn record_remove(&mut self, location: Location) {
+        let block = self.block_mut(location.block);
+        assert_ne!(
+            block.last().copied().flatten(),
+            Some(location.statement_index),
+            "cannot remove the terminator of {:?}",
+            location.block,
+        );
+        for entry in block.iter_mut() {
+            match *entry {
+                Some(index) if index == location.statement_index => *entry = None,
+                Some(index) if index > location.statement_index => *entry = Some(index - 1),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the location that `old` refers to after all recorded edits, or `None` if the
+    /// statement at `old` was removed.
+    pub fn map_location(&self, old: Location) -> Option<Location> {
+        let Some(block) = self.blocks.get(&old.block) else {
+            return Some(old);
+        };
+        let statement_index = (*block.get(old.statement_index)?)?;
+        Some(Location { block: old.block, statement_index })
+    }
+}
+
+/// Translates the pre-edit `locations` to locations in the edited `body`, dropping the
+/// locations whose statement was removed.
+pub fn renumber_locations(body: &Body<'_>, map: &LocationMap, locations: &mut Vec<Location>) {
+    locations.retain_mut(|location| {
+        let Some(new_location) = map.map_location(*location) else {
+            return false;
+        };
+        debug_assert_eq!(
+            Location::checked(body, new_location.block, new_location.statement_index),
+            Some(new_location),
+            "renumbered location does not exist in the edited body",
+        );
+        *location = new_location;
+        true
+    });
+}
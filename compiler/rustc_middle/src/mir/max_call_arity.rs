@@ -0,0 +1,37 @@
+//! Computes the largest number of arguments passed by a call of a MIR body.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Location, Terminator, TerminatorKind};
+
+/// Finds the largest number of arguments that a `Call` or `TailCall` terminator of a body
+/// passes, e.g. to estimate how much stack its outgoing calls need.
+///
+/// Arguments are counted as they appear in the MIR, before the ABI is applied: the spread
+/// arguments of the `"rust-call"` ABI count as one tuple, and an argument passed in several
+/// registers or indirectly still counts once. Calls in cleanup blocks are counted too, while
+/// `Drop` terminators and inline assembly are not calls.
+pub struct MaxCallArity {
+    max: usize,
+}
+
+impl MaxCallArity {
+    /// Returns the largest number of arguments passed by a call of `body`, or 0 if it makes no
+    /// calls.
+    pub fn compute(body: &Body<'_>) -> usize {
+        let mut visitor = MaxCallArity { max: 0 };
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            visitor.visit_terminator(data.terminator(), body.terminator_loc(block));
+        }
+        visitor.max
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for MaxCallArity {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, _location: Location) {
+        if let TerminatorKind::Call { args, .. } | TerminatorKind::TailCall { args, .. } =
+            &terminator.kind
+        {
+            self.max = self.max.max(args.len());
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Collects the length assumptions that slice and array patterns make about the places they
+//! match on.
+
+use rustc_data_structures::fx::{FxIndexMap, FxIndexSet};
+
+use crate::mir::visit::{PlaceContext, Visitor};
+use crate::mir::{Body, Location, PlaceElem, PlaceRef};
+
+/// Aggregates, for every array or slice place, the `min_length`s of the `ConstantIndex`
+/// projections applied to it.
+///
+/// When indexing an array, `min_length` is the exact length of the array, so an array place
+/// with more than one distinct `min_length` has contradictory length assumptions. For slices,
+/// every `min_length` is a lower bound and several of them are expected, one per slice
+/// pattern that binds an element.
+pub struct MinLengthConstraints<'tcx> {
+    /// The place projected by the `ConstantIndex` that is being visited.
+    base: Option<PlaceRef<'tcx>>,
+    pub constraints: FxIndexMap<PlaceRef<'tcx>, FxIndexSet<u64>>,
+}
+
+impl<'tcx> MinLengthConstraints<'tcx> {
+    pub fn new() -> Self {
+        MinLengthConstraints { base: None, constraints: FxIndexMap::default() }
+    }
+
+    /// Returns the `min_length` constraints of every place of `body`, in visiting order.
+    pub fn collect(body: &Body<'tcx>) -> FxIndexMap<PlaceRef<'tcx>, FxIndexSet<u64>> {
+        let mut visitor = MinLengthConstraints::new();
+        visitor.visit_body(body);
+        visitor.constraints
+    }
+}
+
+impl<'tcx> Default for MinLengthConstraints<'tcx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for MinLengthConstraints<'tcx> {
+    fn visit_projection_elem(
+        &mut self,
+        place_ref: PlaceRef<'tcx>,
+        elem: PlaceElem<'tcx>,
+        context: PlaceContext,
+        location: Location,
+    ) {
+        self.base = Some(place_ref);
+        self.super_projection_elem(place_ref, elem, context, location);
+        self.base = None;
+    }
+
+    fn visit_constant_index(
+        &mut self,
+        _offset: u64,
+        min_length: u64,
+        _from_end: bool,
+        _location: Location,
+    ) {
+        let base = self.base.expect("`ConstantIndex` visited outside of a projection");
+        self.constraints.entry(base).or_default().insert(min_length);
+    }
+}
@@ -51,24 +51,64 @@
 pub use basic_blocks::BasicBlocks;
 
 mod basic_blocks;
+pub mod borrow_sites;
+pub mod call_abi;
+pub mod call_graph;
+pub mod capture_field_uses;
+pub mod const_allocs;
 mod consts;
 pub mod coverage;
+pub mod cross_yield;
+pub mod dead_stores;
+pub mod drop_flag_uses;
+pub mod drop_glue_calls;
+pub mod escaping_locals;
+pub mod free_regions;
 mod generic_graph;
 pub mod generic_graphviz;
 pub mod graphviz;
+pub mod inline_cost;
+pub mod instantiate;
 pub mod interpret;
+pub mod leaf_body;
+pub mod local_block_spread;
+pub mod local_use_range;
+pub mod locals_by_origin;
+pub mod location_map;
+pub mod max_call_arity;
+pub mod min_length;
 pub mod mono;
+pub mod needs_drop_locals;
+pub mod panic_freedom;
 pub mod patch;
+pub mod place_substitution;
+pub mod post_divergence;
+pub mod predecessor_counts;
 pub mod pretty;
 mod query;
+pub mod referenced_promoteds;
+pub mod region_refresher;
+pub mod repeat_count;
+pub mod return_slot_uses;
+pub mod self_referential_aggregate;
+pub mod span_collector;
 mod statement;
+pub mod statement_data_flow;
+pub mod static_references;
+pub mod storage_balance;
+pub mod storage_dead_while_borrowed;
+pub mod switch_coverage;
 mod syntax;
 pub mod tcx;
 mod terminator;
 
 pub mod traversal;
 mod type_foldable;
+pub mod types_by_location;
+pub mod unused_locals;
+pub mod unwind_edges;
 pub mod visit;
+pub mod yield_borrows;
 
 pub use self::generic_graph::graphviz_safe_def_name;
 pub use self::graphviz::write_mir_graphviz;
@@ -613,7 +653,7 @@ pub fn span_for_ty_context(&self, ty_context: TyContext) -> Span {
             | TyContext::LocalDecl { source_info, .. }
             | TyContext::YieldTy(source_info)
             | TyContext::ResumeTy(source_info) => source_info.span,
-            TyContext::Location(loc) => self.source_info(loc).span,
+            TyContext::Location(loc) | TyContext::ConstArg(loc) => self.source_info(loc).span,
         }
     }
 
@@ -1132,6 +1172,9 @@ pub enum LocalInfo<'tcx> {
     BlockTailTemp(BlockTailInfo),
     /// A temporary created during the pass `Derefer` to avoid it's retagging
     DerefTemp,
+    /// A boolean created during the pass `ElaborateDrops` to track whether a place must be
+    /// dropped, when that can't be known statically.
+    DropFlag,
     /// A temporary created for borrow checking.
     FakeBorrow,
     /// A local without anything interesting about it.
@@ -1218,6 +1261,11 @@ pub fn is_deref_temp(&self) -> bool {
         return false;
     }
 
+    /// Returns `true` if this is a drop flag, see `LocalInfo::DropFlag`.
+    pub fn is_drop_flag(&self) -> bool {
+        matches!(self.local_info(), LocalInfo::DropFlag)
+    }
+
     /// Returns `true` is the local is from a compiler desugaring, e.g.,
     /// `__next` from a `for` loop.
     #[inline]
@@ -1541,6 +1589,9 @@ pub struct SourceScopeData<'tcx> {
     pub local_data: ClearCrossCrate<SourceScopeLocalData>,
 }
 
+/// Source scopes don't record whether they are in an `unsafe` block: unsafety is checked on
+/// THIR, by the `check_unsafety` query, before MIR is built. Passes that need to know which
+/// statements come from `unsafe` code have to go back to the `safety_mode` of THIR blocks.
 #[derive(Clone, Debug, TyEncodable, TyDecodable, HashStable)]
 pub struct SourceScopeLocalData {
     /// An `HirId` with lint levels equivalent to this scope's lint levels.
@@ -1728,6 +1779,20 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
 impl Location {
     pub const START: Location = Location { block: START_BLOCK, statement_index: 0 };
 
+    /// Builds a `Location`, returning `None` if it does not exist in `body`.
+    ///
+    /// `block` must be a basic block of `body`, and `statement_index` must be at most the
+    /// number of statements in that block. A `statement_index` equal to the number of
+    /// statements refers to the terminator.
+    pub fn checked<'tcx>(
+        body: &Body<'tcx>,
+        block: BasicBlock,
+        statement_index: usize,
+    ) -> Option<Location> {
+        let data = body.basic_blocks.get(block)?;
+        (statement_index <= data.statements.len()).then_some(Location { block, statement_index })
+    }
+
     /// Returns the location immediately after this one within the enclosing block.
     ///
     /// Note that if this location represents a terminator, then the
@@ -0,0 +1,43 @@
+//! Finds the locals of a MIR body whose type needs to be dropped.
+
+use rustc_index::bit_set::BitSet;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Local, LocalDecl};
+use crate::ty::{self, TyCtxt};
+
+/// Collects the locals whose type may have drop glue, as decided by [`ty::Ty::needs_drop`].
+///
+/// This is conservative: a type that depends on a generic parameter is reported as needing
+/// drop unless `param_env` proves that it doesn't, e.g. with a `T: Copy` bound. Whether the
+/// local is ever initialized, or already moved out of when it goes out of scope, is not taken
+/// into account.
+pub struct NeedsDropLocals<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    locals: BitSet<Local>,
+}
+
+impl<'tcx> NeedsDropLocals<'tcx> {
+    /// Returns the set of locals of `body` whose type needs drop in `param_env`.
+    pub fn compute(
+        tcx: TyCtxt<'tcx>,
+        param_env: ty::ParamEnv<'tcx>,
+        body: &Body<'tcx>,
+    ) -> BitSet<Local> {
+        let locals = BitSet::new_empty(body.local_decls.len());
+        let mut visitor = NeedsDropLocals { tcx, param_env, locals };
+        for (local, local_decl) in body.local_decls.iter_enumerated() {
+            visitor.visit_local_decl(local, local_decl);
+        }
+        visitor.locals
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for NeedsDropLocals<'tcx> {
+    fn visit_local_decl(&mut self, local: Local, local_decl: &LocalDecl<'tcx>) {
+        if local_decl.ty.needs_drop(self.tcx, self.param_env) {
+            self.locals.insert(local);
+        }
+    }
+}
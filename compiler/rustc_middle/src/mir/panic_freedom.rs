@@ -0,0 +1,127 @@
+//! Looks for the sources of panics in a MIR body.
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_hir::LangItem;
+use rustc_span::sym;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{traversal, AssertKind, Body, Location, Terminator, TerminatorKind};
+use crate::ty::TyCtxt;
+
+/// Why a body may panic, as found by [`PanicFreedom`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PanicSource {
+    /// An `Assert` terminator checking that an index is in bounds.
+    BoundsCheck,
+    /// An `Assert` terminator checking an arithmetic operation, for overflow or for a division
+    /// by zero.
+    Overflow,
+    /// Any other `Assert` terminator, like the checks for resuming a finished coroutine or for
+    /// dereferencing a misaligned pointer.
+    OtherAssert,
+    /// A call to one of the panic functions of the standard library: a panic lang item, or a
+    /// diverging function of the `panicking` module of `core` or `std`, like `panic_display`.
+    ExplicitPanic(DefId),
+    /// An `Unreachable` terminator.
+    Unreachable,
+}
+
+/// Finds the first source of panics of a body, in reverse postorder.
+///
+/// This is a syntactic check of the body itself, not a proof that it can't panic: it doesn't
+/// look at the functions that the body calls, including drop glue, so a body that calls a
+/// function which panics is considered panic-free. Conversely, a source of panics that is
+/// guarded by a condition that is never true is still reported. Cleanup blocks, which only
+/// run once the body is already unwinding, are not inspected.
+pub struct PanicFreedom<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    found: Option<PanicSource>,
+}
+
+impl<'tcx> PanicFreedom<'tcx> {
+    /// Returns the first source of panics of `body`, or `None` if it has none.
+    pub fn check(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Option<PanicSource> {
+        let mut visitor = PanicFreedom { tcx, found: None };
+        for (block, data) in traversal::reverse_postorder(body) {
+            if data.is_cleanup {
+                continue;
+            }
+            visitor.visit_basic_block_data(block, data);
+            if visitor.found.is_some() {
+                break;
+            }
+        }
+        visitor.found
+    }
+
+    fn is_panic_fn(&self, def_id: DefId) -> bool {
+        let tcx = self.tcx;
+        if let Some(item) = tcx.lang_items().from_def_id(def_id) {
+            return matches!(
+                item,
+                LangItem::Panic
+                    | LangItem::PanicNounwind
+                    | LangItem::PanicFmt
+                    | LangItem::ConstPanicFmt
+                    | LangItem::PanicBoundsCheck
+                    | LangItem::PanicMisalignedPointerDereference
+                    | LangItem::PanicImpl
+                    | LangItem::PanicCannotUnwind
+                    | LangItem::PanicInCleanup
+                    | LangItem::PanicAddOverflow
+                    | LangItem::PanicSubOverflow
+                    | LangItem::PanicMulOverflow
+                    | LangItem::PanicDivOverflow
+                    | LangItem::PanicRemOverflow
+                    | LangItem::PanicNegOverflow
+                    | LangItem::PanicShrOverflow
+                    | LangItem::PanicShlOverflow
+                    | LangItem::PanicDivZero
+                    | LangItem::PanicRemZero
+                    | LangItem::PanicCoroutineResumed
+                    | LangItem::PanicAsyncFnResumed
+                    | LangItem::PanicAsyncGenFnResumed
+                    | LangItem::PanicGenFnNone
+                    | LangItem::PanicCoroutineResumedPanic
+                    | LangItem::PanicAsyncFnResumedPanic
+                    | LangItem::PanicAsyncGenFnResumedPanic
+                    | LangItem::PanicGenFnNonePanic
+                    | LangItem::BeginPanic
+            );
+        }
+        // The other entry points, like `panic_display`, `panic_str_2015` or `assert_failed`, are
+        // not lang items, but they all diverge and live in the `panicking` modules.
+        matches!(tcx.crate_name(def_id.krate), sym::core | sym::std)
+            && tcx.def_kind(def_id) == DefKind::Fn
+            && tcx.opt_parent(def_id).and_then(|parent| tcx.opt_item_name(parent))
+                == Some(sym::panicking)
+            && tcx.fn_sig(def_id).skip_binder().output().skip_binder().is_never()
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for PanicFreedom<'tcx> {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, _location: Location) {
+        let source = match &terminator.kind {
+            TerminatorKind::Assert { msg, .. } => match **msg {
+                AssertKind::BoundsCheck { .. } => PanicSource::BoundsCheck,
+                AssertKind::Overflow(..)
+                | AssertKind::OverflowNeg(..)
+                | AssertKind::DivisionByZero(..)
+                | AssertKind::RemainderByZero(..) => PanicSource::Overflow,
+                AssertKind::ResumedAfterReturn(..)
+                | AssertKind::ResumedAfterPanic(..)
+                | AssertKind::MisalignedPointerDereference { .. } => PanicSource::OtherAssert,
+            },
+            TerminatorKind::Call { func, .. } | TerminatorKind::TailCall { func, .. } => match func
+                .const_fn_def()
+            {
+                Some((def_id, _)) if self.is_panic_fn(def_id) => PanicSource::ExplicitPanic(def_id),
+                _ => return,
+            },
+            TerminatorKind::Unreachable => PanicSource::Unreachable,
+            _ => return,
+        };
+        self.found.get_or_insert(source);
+    }
+}
@@ -0,0 +1,55 @@
+//! Replaces a place with another one in a range of blocks of a MIR body.
+
+use std::ops::RangeInclusive;
+
+use crate::mir::visit::{MutVisitor, PlaceContext};
+use crate::mir::{BasicBlock, Location, Place, VarDebugInfo};
+use crate::ty::TyCtxt;
+
+/// Replaces `from` with `to` in every place of the blocks in `range`, e.g. `(*_3).0` with `_5`
+/// in `bb2..=bb4` once it is known that they hold the same value there.
+///
+/// The places that have `from` as a prefix are rewritten too, keeping the rest of their
+/// projections: with the replacement above, `(*_3).0.1` becomes `_5.1`. The places in the
+/// blocks outside of `range`, and the locals used as indices, are left untouched. So is the
+/// debuginfo: it describes a variable in its whole scope rather than in some blocks, and its
+/// places are only visited at `Location::START`, which doesn't say where they are used.
+///
+/// This doesn't check that the replacement is correct: `to` must have the same type as `from`,
+/// and hold the same value wherever it is used, including when the place is written to.
+pub struct ScopedPlaceSubstitutor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub from: Place<'tcx>,
+    pub to: Place<'tcx>,
+    pub range: RangeInclusive<BasicBlock>,
+}
+
+impl<'tcx> ScopedPlaceSubstitutor<'tcx> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        from: Place<'tcx>,
+        to: Place<'tcx>,
+        range: RangeInclusive<BasicBlock>,
+    ) -> Self {
+        ScopedPlaceSubstitutor { tcx, from, to, range }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for ScopedPlaceSubstitutor<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_place(&mut self, place: &mut Place<'tcx>, context: PlaceContext, location: Location) {
+        if self.range.contains(&location.block)
+            && place.local == self.from.local
+            && place.projection.starts_with(&self.from.projection)
+        {
+            let rest = &place.projection[self.from.projection.len()..];
+            *place = self.to.project_deeper(rest, self.tcx);
+        }
+        self.super_place(place, context, location);
+    }
+
+    fn visit_var_debug_info(&mut self, _var_debug_info: &mut VarDebugInfo<'tcx>) {}
+}
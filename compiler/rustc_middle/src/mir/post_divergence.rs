@@ -0,0 +1,48 @@
+//! Finds the statements of a MIR body that can't be executed because their block diverges.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{BasicBlock, BasicBlockData, Body, Location, StatementKind, TerminatorKind};
+
+/// Collects the statements that precede an `Unreachable` terminator in their block.
+///
+/// A statement can never follow a diverging terminator in the same block, since the
+/// terminator ends the block, and a `Call` that never returns, with no `target`, has no
+/// successor either. The statements that are dead because of a divergence are therefore the
+/// ones of blocks that end in `Unreachable`: executing them would mean reaching the
+/// terminator, so they can't be executed either. Passes like `UnreachablePropagation` remove
+/// them, so finding some in optimized MIR means that such a pass missed them, or that a block
+/// was made unreachable without its statements being removed.
+///
+/// Storage markers and `Nop`s have no effect, so they are not reported.
+pub struct PostDivergenceChecker {
+    dead: Vec<Location>,
+}
+
+impl PostDivergenceChecker {
+    /// Returns the location of every statement of `body` that precedes an `Unreachable`
+    /// terminator in its block, in the order of the blocks.
+    pub fn check<'tcx>(body: &Body<'tcx>) -> Vec<Location> {
+        let mut visitor = PostDivergenceChecker { dead: Vec::new() };
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            visitor.visit_basic_block_data(block, data);
+        }
+        visitor.dead
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for PostDivergenceChecker {
+    fn visit_basic_block_data(&mut self, block: BasicBlock, data: &BasicBlockData<'tcx>) {
+        if let TerminatorKind::Unreachable = data.terminator().kind {
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                if !matches!(
+                    statement.kind,
+                    StatementKind::StorageLive(_)
+                        | StatementKind::StorageDead(_)
+                        | StatementKind::Nop
+                ) {
+                    self.dead.push(Location { block, statement_index });
+                }
+            }
+        }
+    }
+}
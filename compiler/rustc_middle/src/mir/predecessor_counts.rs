@@ -0,0 +1,41 @@
+//! Counts the predecessors of each basic block of a MIR body.
+
+use rustc_index::IndexVec;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{BasicBlock, Body, Location, Terminator, UnwindAction};
+
+/// Returns the number of incoming edges of every block of `body`.
+///
+/// An edge is counted once per occurrence in a terminator, so a block that is the target of two
+/// arms of a `SwitchInt` has two predecessors even though it has a single predecessor block,
+/// as in [`BasicBlocks::predecessors`](crate::mir::BasicBlocks::predecessors). Edges to
+/// cleanup blocks, i.e. `UnwindAction::Cleanup`, are counted if `include_unwind` is true, but
+/// the imaginary edges of `FalseEdge` and `FalseUnwind` and the drop edges of `Yield` are
+/// always counted.
+pub fn predecessor_counts(body: &Body<'_>, include_unwind: bool) -> IndexVec<BasicBlock, usize> {
+    let mut visitor =
+        PredecessorCounts { counts: IndexVec::from_elem(0, &body.basic_blocks), include_unwind };
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        visitor.visit_basic_block_data(block, data);
+    }
+    visitor.counts
+}
+
+struct PredecessorCounts {
+    counts: IndexVec<BasicBlock, usize>,
+    include_unwind: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for PredecessorCounts {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, _location: Location) {
+        for target in terminator.successors() {
+            self.counts[target] += 1;
+        }
+        if !self.include_unwind
+            && let Some(&UnwindAction::Cleanup(cleanup)) = terminator.unwind()
+        {
+            self.counts[cleanup] -= 1;
+        }
+    }
+}
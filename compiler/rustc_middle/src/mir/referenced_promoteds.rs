@@ -0,0 +1,56 @@
+//! Finds the promoted constants that are used by a MIR body.
+
+use rustc_hir::def_id::DefId;
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexSlice;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Const, ConstOperand, Location, Promoted};
+
+/// Collects the promoted constants of a body that are referenced by the body itself, or by one
+/// of the promoteds it references, transitively.
+///
+/// A promoted can reference other promoteds of the same body, e.g. `&&1` promotes both `&1`
+/// and the reference to it. The promoteds that are not in the returned set are dead, and can
+/// be removed once the remaining indices are renumbered.
+pub struct ReferencedPromoteds {
+    /// The item the promoteds belong to. Constants with a promoted index for another item are
+    /// not references to these promoteds.
+    def_id: DefId,
+    referenced: BitSet<Promoted>,
+    /// The promoteds that were found but whose own body hasn't been visited yet.
+    pending: Vec<Promoted>,
+}
+
+impl ReferencedPromoteds {
+    /// Returns the indices of the promoteds of `body` that it references, given the bodies
+    /// of all its promoteds.
+    pub fn collect<'tcx>(
+        body: &Body<'tcx>,
+        promoted: &IndexSlice<Promoted, Body<'tcx>>,
+    ) -> BitSet<Promoted> {
+        let mut visitor = ReferencedPromoteds {
+            def_id: body.source.def_id(),
+            referenced: BitSet::new_empty(promoted.len()),
+            pending: Vec::new(),
+        };
+        visitor.visit_body(body);
+        while let Some(index) = visitor.pending.pop() {
+            visitor.visit_body(&promoted[index]);
+        }
+        visitor.referenced
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for ReferencedPromoteds {
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, location: Location) {
+        if let Const::Unevaluated(uv, _) = constant.const_
+            && let Some(index) = uv.promoted
+            && uv.def == self.def_id
+            && self.referenced.insert(index)
+        {
+            self.pending.push(index);
+        }
+        self.super_const_operand(constant, location);
+    }
+}
@@ -0,0 +1,79 @@
+//! Replaces the erased regions of a MIR body with fresh placeholder regions.
+
+use crate::mir::visit::{MutVisitor, TyContext};
+use crate::mir::{ConstOperand, Location};
+use crate::ty::{self, GenericArgsRef, Ty, TyCtxt, TypeFoldable};
+
+/// Gives every erased region of a body its own placeholder region, e.g. to check the regions
+/// of optimized MIR again.
+///
+/// This is a best-effort inverse of region erasure: the regions that erasure merged are all
+/// distinct afterwards, and nothing relates them to each other or to the regions of the
+/// signature, so they must be constrained again by whoever uses them, e.g. by replacing them
+/// with inference variables. The placeholders all live in the universe after the root one, and
+/// are numbered in the order of the `MutVisitor` traversal, one per erased occurrence, so
+/// refreshing the same body twice gives the same regions. The body is meant to be analyzed
+/// afterwards, not codegened. The return type is refreshed through the declaration of the
+/// return place only.
+pub struct RegionRefresher<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    next_var: u32,
+}
+
+impl<'tcx> RegionRefresher<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        RegionRefresher { tcx, next_var: 0 }
+    }
+
+    /// Returns how many fresh regions have been created, i.e. how many erased regions have
+    /// been replaced. The fresh regions are numbered from 0 to this number.
+    pub fn fresh_regions(&self) -> u32 {
+        self.next_var
+    }
+
+    fn refresh<T: TypeFoldable<TyCtxt<'tcx>>>(&mut self, value: T) -> T {
+        self.tcx.fold_regions(value, |region, _| {
+            if !region.is_erased() {
+                return region;
+            }
+            let var = ty::BoundVar::from_u32(self.next_var);
+            self.next_var += 1;
+            ty::Region::new_placeholder(
+                self.tcx,
+                ty::PlaceholderRegion {
+                    universe: ty::UniverseIndex::ROOT.next_universe(),
+                    bound: ty::BoundRegion { var, kind: ty::BrAnon },
+                },
+            )
+        })
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for RegionRefresher<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>, ty_context: TyContext) {
+        // The return type is a copy of the type of the return place, which is visited too.
+        if !matches!(ty_context, TyContext::ReturnTy(_)) {
+            *ty = self.refresh(*ty);
+        }
+    }
+
+    fn visit_args(&mut self, args: &mut GenericArgsRef<'tcx>, _location: Location) {
+        *args = self.refresh(*args);
+    }
+
+    fn visit_region(&mut self, region: &mut ty::Region<'tcx>, _location: Location) {
+        *region = self.refresh(*region);
+    }
+
+    fn visit_ty_const(&mut self, ct: &mut ty::Const<'tcx>, _location: Location) {
+        *ct = self.refresh(*ct);
+    }
+
+    fn visit_const_operand(&mut self, constant: &mut ConstOperand<'tcx>, _location: Location) {
+        constant.const_ = self.refresh(constant.const_);
+    }
+}
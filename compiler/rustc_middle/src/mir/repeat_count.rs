@@ -0,0 +1,41 @@
+//! Instantiates the counts of `Repeat` rvalues of a generic MIR body.
+
+use crate::mir::visit::MutVisitor;
+use crate::mir::{Location, Rvalue};
+use crate::ty::{self, GenericArgsRef, TyCtxt};
+
+/// Instantiates the count of every `Rvalue::Repeat` with `args`, so that `[x; N]` becomes
+/// `[x; 3]` when specializing for `N = 3`.
+///
+/// A count is only replaced if it evaluates to a value once instantiated. A count that is still
+/// symbolic, because `args` maps its parameters to other parameters or because it is an
+/// expression that can't be evaluated yet, is left unchanged. Nothing else in the body is
+/// instantiated, so the types of locals still mention the parameters.
+pub struct RepeatCountSubstitutor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub args: GenericArgsRef<'tcx>,
+}
+
+impl<'tcx> RepeatCountSubstitutor<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, args: GenericArgsRef<'tcx>) -> Self {
+        RepeatCountSubstitutor { tcx, args }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for RepeatCountSubstitutor<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Repeat(_, count) = rvalue {
+            let instantiated = ty::EarlyBinder::bind(*count)
+                .instantiate(self.tcx, self.args)
+                .normalize(self.tcx, ty::ParamEnv::reveal_all());
+            if let ty::ConstKind::Value(..) = instantiated.kind() {
+                *count = instantiated;
+            }
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
@@ -0,0 +1,41 @@
+//! Collects the places of a MIR body that are based on the return place.
+
+use crate::mir::visit::{NonMutatingUseContext, PlaceContext, Visitor};
+use crate::mir::{Body, Location, Place, Terminator, TerminatorKind, RETURN_PLACE};
+
+/// Collects every place whose local is `RETURN_PLACE`, with the context and location in which
+/// it is used. Places projecting from the return place, like `_0.0` or `(*_0)`, are included,
+/// as are the uses that are neither reads nor writes, like debuginfo.
+///
+/// Every `Return` terminator also counts as a move out of `_0`, since that is how the return
+/// value gets to the caller, even though the terminator doesn't mention `_0`. A use of `_0`
+/// as an index, as in `_1[_0]`, is not a place based on the return place and is not collected.
+pub struct ReturnSlotUses<'tcx> {
+    pub uses: Vec<(Place<'tcx>, PlaceContext, Location)>,
+}
+
+impl<'tcx> ReturnSlotUses<'tcx> {
+    /// Returns the uses of the return place of `body`, in visiting order.
+    pub fn collect(body: &Body<'tcx>) -> Vec<(Place<'tcx>, PlaceContext, Location)> {
+        let mut visitor = ReturnSlotUses { uses: Vec::new() };
+        visitor.visit_body(body);
+        visitor.uses
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for ReturnSlotUses<'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if place.local == RETURN_PLACE {
+            self.uses.push((*place, context, location));
+        }
+        self.super_place(place, context, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Return = terminator.kind {
+            let context = PlaceContext::NonMutatingUse(NonMutatingUseContext::Move);
+            self.uses.push((Place::return_place(), context, location));
+        }
+        self.super_terminator(terminator, location);
+    }
+}
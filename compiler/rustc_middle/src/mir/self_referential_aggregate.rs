@@ -0,0 +1,52 @@
+//! Finds aggregates that are built from the place they are assigned to.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{AggregateKind, Body, FieldIdx, Location, Operand, Place, ProjectionElem, Rvalue};
+
+/// Collects the locations of the assignments `dest = Aggregate(..)` where one of the operands
+/// of the aggregate is a place based on the local of `dest`, e.g. `_1 = (move _1, const 1_i32)`.
+///
+/// Reading a field of the destination into the same field, as in
+/// `_1 = S { a: const 1_i32, b: copy (_1.1: i32) }`, which is how functional record update
+/// (`S { a: 1, ..x }` assigned to `x`) is built, is benign and is not reported. An update that
+/// goes through a temporary, like `_2 = copy (_1.0: i32); _1 = (move _2, const 1_i32)`, is not
+/// reported either, since the aggregate doesn't mention `_1`.
+pub struct SelfReferentialAggregate {
+    pub locations: Vec<Location>,
+}
+
+impl SelfReferentialAggregate {
+    /// Returns the locations of the self-referential aggregates of `body`, in visiting order.
+    pub fn collect(body: &Body<'_>) -> Vec<Location> {
+        let mut visitor = SelfReferentialAggregate { locations: Vec::new() };
+        visitor.visit_body(body);
+        visitor.locations
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for SelfReferentialAggregate {
+    fn visit_assign(&mut self, dest: &Place<'tcx>, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Aggregate(kind, operands) = rvalue {
+            // Only the fields of a union aggregate don't map to the operand indices.
+            let is_union = matches!(**kind, AggregateKind::Adt(_, _, _, _, Some(_)));
+            let self_referential = operands.iter_enumerated().any(|(field, operand)| {
+                let (Operand::Copy(place) | Operand::Move(place)) = operand else {
+                    return false;
+                };
+                place.local == dest.local && (is_union || !is_same_field(dest, place, field))
+            });
+            if self_referential {
+                self.locations.push(location);
+            }
+        }
+        self.super_assign(dest, rvalue, location);
+    }
+}
+
+/// Returns whether `place` is exactly field `field` of `dest`.
+fn is_same_field(dest: &Place<'_>, place: &Place<'_>, field: FieldIdx) -> bool {
+    match place.projection.split_last() {
+        Some((&ProjectionElem::Field(f, _), base)) => f == field && base == &dest.projection[..],
+        _ => false,
+    }
+}
@@ -0,0 +1,96 @@
+//! Collects the spans of a MIR body, e.g. for source-mapping tools.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_span::Span;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{
+    Body, ConstOperand, Local, LocalDecl, Location, SourceScopeData, Statement, Terminator,
+    VarDebugInfo,
+};
+
+/// The kind of MIR node that a span collected by [`SpanCollector`] belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SpanOrigin {
+    Statement,
+    Terminator,
+    /// The span of the callee and arguments of a call, see `Visitor::visit_fn_span`.
+    FnSpan,
+    Constant,
+    Scope,
+    LocalDecl,
+    VarDebugInfo,
+    /// Any other span, like the span of the body itself or of a user type annotation.
+    Other,
+}
+
+/// Collects every distinct span of a body, together with the kind of node it came from.
+///
+/// A span that is attached to several kinds of nodes is reported once for each kind.
+pub struct SpanCollector {
+    origin: SpanOrigin,
+    pub spans: FxIndexSet<(Span, SpanOrigin)>,
+}
+
+impl SpanCollector {
+    pub fn new() -> Self {
+        SpanCollector { origin: SpanOrigin::Other, spans: FxIndexSet::default() }
+    }
+
+    /// Returns the spans of `body`, in visiting order.
+    pub fn collect<'tcx>(body: &Body<'tcx>) -> FxIndexSet<(Span, SpanOrigin)> {
+        let mut collector = SpanCollector::new();
+        collector.visit_body(body);
+        collector.spans
+    }
+
+    fn with_origin(&mut self, origin: SpanOrigin, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.origin, origin);
+        f(self);
+        self.origin = outer;
+    }
+}
+
+impl Default for SpanCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for SpanCollector {
+    fn visit_span(&mut self, span: Span) {
+        self.spans.insert((span, self.origin));
+    }
+
+    fn visit_statement(&mut self, statement: &Statement<'tcx>, location: Location) {
+        self.with_origin(SpanOrigin::Statement, |this| this.super_statement(statement, location));
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        self.with_origin(SpanOrigin::Terminator, |this| {
+            this.super_terminator(terminator, location)
+        });
+    }
+
+    fn visit_fn_span(&mut self, fn_span: Span, _location: Location) {
+        self.spans.insert((fn_span, SpanOrigin::FnSpan));
+    }
+
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, location: Location) {
+        self.with_origin(SpanOrigin::Constant, |this| this.super_const_operand(constant, location));
+    }
+
+    fn visit_source_scope_data(&mut self, scope_data: &SourceScopeData<'tcx>) {
+        self.with_origin(SpanOrigin::Scope, |this| this.super_source_scope_data(scope_data));
+    }
+
+    fn visit_local_decl(&mut self, local: Local, local_decl: &LocalDecl<'tcx>) {
+        self.with_origin(SpanOrigin::LocalDecl, |this| this.super_local_decl(local, local_decl));
+    }
+
+    fn visit_var_debug_info(&mut self, var_debug_info: &VarDebugInfo<'tcx>) {
+        self.with_origin(SpanOrigin::VarDebugInfo, |this| {
+            this.super_var_debug_info(var_debug_info)
+        });
+    }
+}
@@ -0,0 +1,49 @@
+//! Computes the locals that a single statement writes and reads.
+
+use smallvec::SmallVec;
+
+use crate::mir::visit::{PlaceContext, Visitor};
+use crate::mir::{Local, Location, Statement};
+
+/// Collects the locals defined and used by a statement, as classified by [`PlaceContext`]:
+/// a local is defined if it has a mutating use, like being assigned to or mutably
+/// borrowed, and used if it has a non-mutating use. A local can be both, e.g. in
+/// `_1 = Add(copy _1, const 1_i32)`.
+///
+/// Storage markers and debuginfo are neither definitions nor uses, so `StorageLive` and
+/// `StorageDead` statements have empty sets.
+pub struct StatementDataFlow {
+    defs: SmallVec<[Local; 2]>,
+    uses: SmallVec<[Local; 4]>,
+}
+
+impl StatementDataFlow {
+    /// Returns the locals defined and the locals used by `statement`, each without
+    /// duplicates and in visiting order.
+    pub fn compute<'tcx>(
+        statement: &Statement<'tcx>,
+        location: Location,
+    ) -> (SmallVec<[Local; 2]>, SmallVec<[Local; 4]>) {
+        let mut visitor = StatementDataFlow { defs: SmallVec::new(), uses: SmallVec::new() };
+        visitor.visit_statement(statement, location);
+        (visitor.defs, visitor.uses)
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for StatementDataFlow {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, _: Location) {
+        match context {
+            PlaceContext::MutatingUse(_) => {
+                if !self.defs.contains(&local) {
+                    self.defs.push(local);
+                }
+            }
+            PlaceContext::NonMutatingUse(_) => {
+                if !self.uses.contains(&local) {
+                    self.uses.push(local);
+                }
+            }
+            PlaceContext::NonUse(_) => {}
+        }
+    }
+}
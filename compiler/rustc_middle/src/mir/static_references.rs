@@ -0,0 +1,53 @@
+//! Finds the statics referenced by the initializer of a static.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_hir::def_id::DefId;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, ConstOperand, Location, Rvalue};
+use crate::ty::TyCtxt;
+
+/// Collects the statics that a body refers to, either through a pointer constant or, for
+/// thread locals, through a `ThreadLocalRef`.
+///
+/// Only direct references are found: statics referenced by the functions or constants that
+/// the body uses are not. To find indirect cycles (`A` refers to `B` which refers to `A`),
+/// the caller has to walk the initializers of the returned statics in turn.
+pub struct StaticSelfReference<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub statics: FxIndexSet<DefId>,
+}
+
+impl<'tcx> StaticSelfReference<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        StaticSelfReference { tcx, statics: FxIndexSet::default() }
+    }
+
+    /// Returns the statics referenced by the initializer of the static `def_id`.
+    pub fn collect(tcx: TyCtxt<'tcx>, def_id: DefId) -> FxIndexSet<DefId> {
+        let mut visitor = StaticSelfReference::new(tcx);
+        visitor.visit_body(tcx.mir_for_ctfe(def_id));
+        visitor.statics
+    }
+
+    /// Returns whether the initializer of the static `def_id` refers to the static itself.
+    pub fn refers_to_itself(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+        Self::collect(tcx, def_id).contains(&def_id)
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for StaticSelfReference<'tcx> {
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, location: Location) {
+        if let Some(def_id) = constant.check_static_ptr(self.tcx) {
+            self.statics.insert(def_id);
+        }
+        self.super_const_operand(constant, location);
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::ThreadLocalRef(def_id) = *rvalue {
+            self.statics.insert(def_id);
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
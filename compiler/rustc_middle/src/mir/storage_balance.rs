@@ -0,0 +1,73 @@
+//! Checks that the `StorageLive` statements of a MIR body are followed by a `StorageDead`.
+
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+
+use crate::mir::visit::{NonUseContext, PlaceContext, Visitor};
+use crate::mir::{Body, Local, Location};
+
+/// Finds the `StorageLive` statements from which no `StorageDead` of the same local can be
+/// reached in the control-flow graph.
+///
+/// Reachability follows every edge, including back-edges and unwind edges, so a local that is
+/// made live at the end of a loop body and dead at the start of the next iteration is
+/// balanced. It is also balanced as soon as a single path reaches a `StorageDead`: paths on
+/// which the storage of the local only ends when the function returns or unwinds are allowed.
+///
+/// A `StorageLive` without any reachable `StorageDead` is allowed by MIR semantics, but keeps
+/// the local's stack slot from being reused for the rest of the function.
+pub struct StorageBalance {
+    lives: Vec<(Local, Location)>,
+    deads: IndexVec<Local, Vec<Location>>,
+}
+
+impl StorageBalance {
+    /// Returns the local and the location of every `StorageLive` of `body` from which no
+    /// `StorageDead` of that local is reachable.
+    pub fn check<'tcx>(body: &Body<'tcx>) -> Vec<(Local, Location)> {
+        let mut visitor = StorageBalance {
+            lives: Vec::new(),
+            deads: IndexVec::from_elem(Vec::new(), &body.local_decls),
+        };
+        visitor.visit_body(body);
+
+        let mut unbalanced = Vec::new();
+        for &(local, live) in &visitor.lives {
+            let deads = &visitor.deads[local];
+            // A `StorageDead` later in the same block.
+            if deads.iter().any(|dead| dead.block == live.block && dead > &live) {
+                continue;
+            }
+            // A `StorageDead` anywhere in a block reachable from the end of this one,
+            // including this block itself through a back-edge.
+            let mut visited = BitSet::new_empty(body.basic_blocks.len());
+            let mut stack: Vec<_> =
+                body.basic_blocks[live.block].terminator().successors().collect();
+            let mut reached = false;
+            while let Some(block) = stack.pop() {
+                if !visited.insert(block) {
+                    continue;
+                }
+                if deads.iter().any(|dead| dead.block == block) {
+                    reached = true;
+                    break;
+                }
+                stack.extend(body.basic_blocks[block].terminator().successors());
+            }
+            if !reached {
+                unbalanced.push((local, live));
+            }
+        }
+        unbalanced
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for StorageBalance {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        match context {
+            PlaceContext::NonUse(NonUseContext::StorageLive) => self.lives.push((local, location)),
+            PlaceContext::NonUse(NonUseContext::StorageDead) => self.deads[local].push(location),
+            _ => {}
+        }
+    }
+}
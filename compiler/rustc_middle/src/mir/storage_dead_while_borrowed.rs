@@ -0,0 +1,91 @@
+//! Looks for the `StorageDead`s of locals that may still be used through a reference.
+
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+
+use crate::mir::dead_stores::DeadStores;
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Local, Location, Operand, Place, Rvalue};
+
+/// Finds the `StorageDead(local)` statements after which a reference to `local` may still be
+/// read, which would then be dangling.
+///
+/// A reference is tracked through the local it is assigned to by an `Rvalue::Ref` of a place
+/// that is not behind a pointer, and through the copies and moves of that local. A
+/// `StorageDead` is reported if one of these locals may be read after it, before being
+/// overwritten or killed itself. Which references a local holds is flow-insensitive, so a local
+/// that holds a reference to a local at some point is assumed to hold it everywhere. References
+/// that are passed through anything else than a local, like a field or the result of a call,
+/// are not tracked, and raw pointers are not references.
+///
+/// This complements borrowck rather than replacing it: borrowck already rejects these uses in
+/// the MIR it checks, so this is meant to catch the bugs of the passes that build or rewrite
+/// MIR, e.g. by moving a `StorageDead` too early or a use of a reference too late.
+pub struct StorageDeadWhileBorrowed {
+    /// For every local, the locals that a reference to it is directly assigned to.
+    borrows: IndexVec<Local, Vec<Local>>,
+    /// For every local, the locals that it is copied or moved to.
+    copies: IndexVec<Local, Vec<Local>>,
+    storage_deads: Vec<(Local, Location)>,
+}
+
+impl StorageDeadWhileBorrowed {
+    /// Returns the `StorageDead`s of `body` after which the local may still be used through a
+    /// reference, with the local.
+    pub fn check<'tcx>(body: &Body<'tcx>) -> Vec<(Local, Location)> {
+        let mut visitor = StorageDeadWhileBorrowed {
+            borrows: IndexVec::from_elem(Vec::new(), &body.local_decls),
+            copies: IndexVec::from_elem(Vec::new(), &body.local_decls),
+            storage_deads: Vec::new(),
+        };
+        visitor.visit_body(body);
+
+        let accesses = DeadStores::new(body);
+        visitor
+            .storage_deads
+            .iter()
+            .copied()
+            .filter(|&(local, location)| {
+                visitor
+                    .holders(local)
+                    .iter()
+                    .any(|holder| accesses.is_read_after(body, holder, location))
+            })
+            .collect()
+    }
+
+    /// Returns the locals that may hold a reference to `local`.
+    fn holders(&self, local: Local) -> BitSet<Local> {
+        let mut holders = BitSet::new_empty(self.copies.len());
+        let mut stack = self.borrows[local].clone();
+        while let Some(holder) = stack.pop() {
+            if holders.insert(holder) {
+                stack.extend_from_slice(&self.copies[holder]);
+            }
+        }
+        holders
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for StorageDeadWhileBorrowed {
+    fn visit_assign(&mut self, place: &Place<'tcx>, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Some(holder) = place.as_local() {
+            match rvalue {
+                Rvalue::Ref(_, _, borrowed) if !borrowed.is_indirect() => {
+                    self.borrows[borrowed.local].push(holder);
+                }
+                Rvalue::Use(Operand::Copy(source) | Operand::Move(source)) => {
+                    if let Some(source) = source.as_local() {
+                        self.copies[source].push(holder);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.super_assign(place, rvalue, location);
+    }
+
+    fn visit_storage_dead(&mut self, local: Local, location: Location) {
+        self.storage_deads.push((local, location));
+    }
+}
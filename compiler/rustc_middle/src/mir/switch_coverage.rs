@@ -0,0 +1,116 @@
+//! Checks that `SwitchInt` terminators cover every value of their discriminant.
+
+use rustc_data_structures::fx::FxHashSet;
+
+use crate::mir::visit::Visitor;
+use crate::mir::{
+    BasicBlock, Body, Location, Operand, Rvalue, StatementKind, SwitchTargets, Terminator,
+    TerminatorKind,
+};
+use crate::ty::{self, TyCtxt};
+
+/// Finds the `SwitchInt` terminators whose targets don't cover all the possible values of
+/// their discriminant.
+///
+/// The otherwise target of a `SwitchInt` covers every value that isn't listed explicitly,
+/// unless it is an empty block that is `Unreachable`. In that case, the listed values must
+/// be exhaustive on their own:
+/// - when switching on a `bool`, both `0` and `1` must be listed;
+/// - when switching on the discriminant of an enum, as read by a `Discriminant` rvalue
+///   right before the switch, the discriminant of every variant that isn't known to be
+///   uninhabited must be listed.
+///
+/// Switches on any other integer are not checked, since listing all of their values
+/// would never be worth it.
+pub struct SwitchCoverageChecker<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    body: &'a Body<'tcx>,
+    /// The blocks whose `SwitchInt` terminator doesn't cover its discriminant.
+    pub uncovered: Vec<BasicBlock>,
+}
+
+impl<'a, 'tcx> SwitchCoverageChecker<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, param_env: ty::ParamEnv<'tcx>, body: &'a Body<'tcx>) -> Self {
+        SwitchCoverageChecker { tcx, param_env, body, uncovered: Vec::new() }
+    }
+
+    /// Returns the blocks of `body` whose `SwitchInt` terminator doesn't cover its
+    /// discriminant.
+    pub fn check(
+        tcx: TyCtxt<'tcx>,
+        param_env: ty::ParamEnv<'tcx>,
+        body: &'a Body<'tcx>,
+    ) -> Vec<BasicBlock> {
+        let mut checker = SwitchCoverageChecker::new(tcx, param_env, body);
+        checker.visit_body(body);
+        checker.uncovered
+    }
+
+    /// Returns the values that `discr`, switched on at the end of `block`, can take, or
+    /// `None` if they are not checked.
+    fn possible_values(&self, block: BasicBlock, discr: &Operand<'tcx>) -> Option<FxHashSet<u128>> {
+        let discr_ty = discr.ty(self.body, self.tcx);
+        if discr_ty.is_bool() {
+            return Some([0, 1].into_iter().collect());
+        }
+
+        let discr_local = discr.place()?.as_local()?;
+        let last_statement = self.body.basic_blocks[block].statements.last()?;
+        let StatementKind::Assign(box (lhs, Rvalue::Discriminant(place))) = last_statement.kind
+        else {
+            return None;
+        };
+        if lhs.as_local() != Some(discr_local) {
+            return None;
+        }
+
+        let enum_ty = place.ty(self.body, self.tcx).ty;
+        let ty::Adt(adt, args) = *enum_ty.kind() else {
+            return None;
+        };
+        if !adt.is_enum() {
+            return None;
+        }
+        let values = adt
+            .variants()
+            .iter_enumerated()
+            .filter(|(_, variant)| {
+                variant
+                    .inhabited_predicate(self.tcx, adt)
+                    .instantiate(self.tcx, args)
+                    .apply_any_module(self.tcx, self.param_env)
+                    != Some(false)
+            })
+            .map(|(index, _)| enum_ty.discriminant_for_variant(self.tcx, index).unwrap().val)
+            .collect();
+        Some(values)
+    }
+
+    fn is_covered(
+        &self,
+        block: BasicBlock,
+        discr: &Operand<'tcx>,
+        targets: &SwitchTargets,
+    ) -> bool {
+        if !self.body.basic_blocks[targets.otherwise()].is_empty_unreachable() {
+            return true;
+        }
+        let Some(possible_values) = self.possible_values(block, discr) else {
+            return true;
+        };
+        let listed_values: FxHashSet<u128> = targets.iter().map(|(value, _)| value).collect();
+        possible_values.is_subset(&listed_values)
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for SwitchCoverageChecker<'a, 'tcx> {
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::SwitchInt { discr, targets } = &terminator.kind
+            && !self.is_covered(location.block, discr, targets)
+        {
+            self.uncovered.push(location.block);
+        }
+        self.super_terminator(terminator, location);
+    }
+}
@@ -708,7 +708,9 @@ pub enum TerminatorKind<'tcx> {
     ///
     /// The `replace` flag indicates whether this terminator was created as part of an assignment.
     /// This should only be used for diagnostic purposes, and does not have any operational
-    /// meaning.
+    /// meaning. Such a `Drop` is already in the expanded form of the former `DropAndReplace`
+    /// terminator: MIR building puts the assignment at the start of both the `target` block and
+    /// the cleanup block of `unwind`, so passes never have to split it themselves.
     Drop { place: Place<'tcx>, target: BasicBlock, unwind: UnwindAction, replace: bool },
 
     /// Roughly speaking, evaluates the `func` operand and the arguments, and starts execution of
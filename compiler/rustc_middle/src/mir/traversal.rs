@@ -373,3 +373,24 @@ fn next(&mut self) -> Option<(BasicBlock, &'a BasicBlockData<'tcx>)> {
         None
     }
 }
+
+/// Calls `f` on every statement of `body` whose kind satisfies `kind_predicate`, together with
+/// its location.
+///
+/// This is a cheaper alternative to a full `Visitor` for analyses that are only interested in a
+/// single kind of statement, e.g. `StorageLive`/`StorageDead`. Statements are visited in the
+/// same order as `Visitor::visit_body` visits them: blocks in index order, and statements in
+/// order within each block.
+pub fn for_each_statement_of_kind<'a, 'tcx>(
+    body: &'a Body<'tcx>,
+    mut kind_predicate: impl FnMut(&StatementKind<'tcx>) -> bool,
+    mut f: impl FnMut(&'a Statement<'tcx>, Location),
+) {
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            if kind_predicate(&statement.kind) {
+                f(statement, Location { block, statement_index });
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Collects the types mentioned by a MIR body, grouped by where they appear.
+
+use rustc_data_structures::fx::FxHashMap;
+
+use crate::mir::visit::{TyContext, Visitor};
+use crate::mir::{Body, Local, Location, RETURN_PLACE};
+use crate::ty::Ty;
+
+/// Every type visited in a body, keyed by the location or the local it belongs to.
+///
+/// The types are recorded as `visit_ty` sees them, so a type that is mentioned several times at
+/// the same location, e.g. by two operands of a call, is recorded as many times, in visiting
+/// order. The types of constant operands are recorded at the location of the operand, along
+/// with the other types of the statement or terminator.
+#[derive(Default)]
+pub struct TypesByLocation<'tcx> {
+    /// The types of the statements and terminators, for `TyContext::Location` and
+    /// `TyContext::ConstArg`.
+    pub by_location: FxHashMap<Location, Vec<Ty<'tcx>>>,
+    /// The declared types of the locals, for `TyContext::LocalDecl`, and the return type of the
+    /// body, for `TyContext::ReturnTy`, which is recorded for the return place.
+    pub by_local: FxHashMap<Local, Vec<Ty<'tcx>>>,
+    /// The types that belong neither to a location nor to a local: the yield and resume types
+    /// of coroutines, and the inferred types of user type annotations.
+    pub other: Vec<(TyContext, Ty<'tcx>)>,
+}
+
+impl<'tcx> TypesByLocation<'tcx> {
+    /// Returns the types mentioned by `body`.
+    pub fn collect(body: &Body<'tcx>) -> TypesByLocation<'tcx> {
+        let mut visitor = TypesByLocation::default();
+        visitor.visit_body(body);
+        visitor
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for TypesByLocation<'tcx> {
+    fn visit_ty(&mut self, ty: Ty<'tcx>, context: TyContext) {
+        match context {
+            TyContext::Location(location) | TyContext::ConstArg(location) => {
+                self.by_location.entry(location).or_default().push(ty)
+            }
+            TyContext::LocalDecl { local, .. } => self.by_local.entry(local).or_default().push(ty),
+            TyContext::ReturnTy(_) => self.by_local.entry(RETURN_PLACE).or_default().push(ty),
+            TyContext::UserTy(_) | TyContext::YieldTy(_) | TyContext::ResumeTy(_) => {
+                self.other.push((context, ty))
+            }
+        }
+    }
+}
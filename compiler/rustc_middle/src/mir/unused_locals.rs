@@ -0,0 +1,38 @@
+//! Finds locals that are declared but never used.
+
+use rustc_index::bit_set::BitSet;
+
+use crate::mir::visit::{PlaceContext, Visitor};
+use crate::mir::{Body, Local, Location};
+
+/// Collects the locals of a body that are used at least once.
+///
+/// Storage markers, debuginfo and user type ascriptions are not uses, see
+/// [`PlaceContext::is_use`].
+pub struct UnusedLocals {
+    used: BitSet<Local>,
+}
+
+impl UnusedLocals {
+    /// Returns the locals of `body` that have a declaration but no use.
+    ///
+    /// The return place and the arguments are never reported, since they are part of the
+    /// signature of the body and are implicitly used by its callers.
+    pub fn compute<'tcx>(body: &Body<'tcx>) -> Vec<Local> {
+        let mut visitor = UnusedLocals { used: BitSet::new_empty(body.local_decls.len()) };
+        visitor.visit_body(body);
+        body.local_decls
+            .indices()
+            .skip(1 + body.arg_count)
+            .filter(|&local| !visitor.used.contains(local))
+            .collect()
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for UnusedLocals {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, _: Location) {
+        if context.is_use() {
+            self.used.insert(local);
+        }
+    }
+}
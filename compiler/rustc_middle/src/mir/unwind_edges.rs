@@ -0,0 +1,39 @@
+//! Checks that the unwind edges of a MIR body lead to cleanup blocks.
+
+use crate::mir::visit::Visitor;
+use crate::mir::{BasicBlock, BasicBlocks, Body, Location, UnwindAction};
+
+/// Finds the terminators whose unwind action is `UnwindAction::Cleanup(target)` where `target`
+/// is not a cleanup block.
+///
+/// This only checks the unwind edges of `Drop`, `Call`, `Assert`, `InlineAsm` and
+/// `FalseUnwind` terminators, the ones visited by `Visitor::visit_unwind_action`. The MIR
+/// validator checks the same property, along with the other invariants of cleanup blocks,
+/// e.g. that they don't have unwind edges themselves.
+pub struct UnwindEdgeChecker<'a, 'tcx> {
+    basic_blocks: &'a BasicBlocks<'tcx>,
+    invalid: Vec<(Location, BasicBlock)>,
+}
+
+impl<'a, 'tcx> UnwindEdgeChecker<'a, 'tcx> {
+    /// Returns the location of every terminator of `body` that unwinds to a block that is not
+    /// a cleanup block, along with that block.
+    pub fn check(body: &'a Body<'tcx>) -> Vec<(Location, BasicBlock)> {
+        let mut checker =
+            UnwindEdgeChecker { basic_blocks: &body.basic_blocks, invalid: Vec::new() };
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            checker.visit_terminator(data.terminator(), body.terminator_loc(block));
+        }
+        checker.invalid
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for UnwindEdgeChecker<'_, 'tcx> {
+    fn visit_unwind_action(&mut self, unwind: &UnwindAction, location: Location) {
+        if let UnwindAction::Cleanup(target) = *unwind
+            && !self.basic_blocks[target].is_cleanup
+        {
+            self.invalid.push((location, target));
+        }
+    }
+}
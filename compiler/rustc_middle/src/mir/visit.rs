@@ -61,9 +61,34 @@
 //! The only place that `_` is acceptable is to match a field (or
 //! variant argument) that does not require visiting, as in
 //! `is_cleanup` above.
+//!
+//! ## Visiting order
+//!
+//! Passes may rely on the order in which the parts of a statement or
+//! terminator are visited, which is the order in which they appear in the
+//! source syntax:
+//!
+//! - for an `Assign`, the destination place is visited before the rvalue;
+//! - for a `Call`, the callee is visited first, then the arguments from left
+//!   to right, and finally the destination place;
+//! - for a `SwitchInt`, the discriminant is the only visited operand, and it
+//!   is visited before the targets;
+//! - for an `Aggregate`, the operands are visited from left to right, after
+//!   the aggregate kind.
+//!
+//! This order is checked by the tests in `visit/tests.rs`, so changing it is a
+//! breaking change for analyses that depend on it.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
+use crate::mir::interpret::{AllocId, Scalar};
 use crate::mir::*;
 use crate::ty::CanonicalUserTypeAnnotation;
+use rustc_ast::InlineAsmOptions;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::CoroutineKind;
+use rustc_span::source_map::Spanned;
 
 macro_rules! make_mir_visitor {
     ($visitor_trait_name:ident, $($mutability:ident)?) => {
@@ -80,6 +105,93 @@ fn visit_body(
 
             extra_body_methods!($($mutability)?);
 
+            /// Called by `super_body` before anything else in the body is visited.
+            fn visit_body_start(
+                &mut self,
+                _body: &Body<'tcx>,
+            ) {}
+
+            /// Called by `super_body` right after `visit_body_start`, with the phase of the
+            /// body. Passes that are only valid at some phases, e.g. before drop elaboration,
+            /// can override this to assert that they are not run at the wrong one.
+            fn visit_phase(
+                &mut self,
+                _phase: MirPhase,
+            ) {}
+
+            /// Called by `super_body` with the coverage information of the body, if it has any:
+            /// the mappings from source regions to the counters and expressions of the
+            /// `Coverage` statements, which are visited separately, as statements. Only bodies
+            /// instrumented by the `InstrumentCoverage` pass with `-Cinstrument-coverage` have
+            /// it.
+            fn visit_coverage_info(
+                &mut self,
+                _coverage_info: & $($mutability)? coverage::FunctionCoverageInfo,
+            ) {}
+
+            /// Called by `super_body` after everything else in the body has been visited.
+            fn visit_body_end(
+                &mut self,
+                _body: &Body<'tcx>,
+            ) {}
+
+            /// Called by `super_body` right after `visit_body_start` if the body is the body of a
+            /// coroutine, with the kind of the coroutine: whether it comes from an `async`,
+            /// `gen` or `async gen` block, function or closure, or is a coroutine literal.
+            fn visit_coroutine_kind(
+                &mut self,
+                _kind: CoroutineKind,
+            ) {}
+
+            /// The order in which `super_body` visits the local declarations of the body.
+            /// Defaults to [`LocalOrder::Index`].
+            fn local_decl_order(&self) -> LocalOrder {
+                LocalOrder::Index
+            }
+
+            /// Whether the `super_*` methods should call `visit_ty` and `visit_args`. If this
+            /// returns false, the types of the body are not visited at all: neither the types
+            /// of local declarations, the return, yield and resume types, nor the types in
+            /// rvalues, constants, projections and debuginfo.
+            ///
+            /// Visiting types is cheap unless `visit_ty` is overridden, but visitors that do
+            /// expensive work on types can still be used for control-flow passes that don't
+            /// care about them by making this return false, e.g. based on a flag.
+            fn visits_types(&self) -> bool {
+                true
+            }
+
+            /// Whether the `super_*` methods should also pass the type arguments of unevaluated
+            /// constants to `visit_ty`, with `TyContext::ConstArg`. This is opt-in, so that the
+            /// types that other visitors see all appear directly in the MIR, and it has no effect
+            /// if `visits_types` returns false.
+            fn visits_const_arg_types(&self) -> bool {
+                false
+            }
+
+            /// Calls `visit_ty` unless `visits_types` returns false. The `super_*` methods pass
+            /// every type through this, so it is not meant to be overridden.
+            fn maybe_visit_ty(
+                &mut self,
+                ty: $(& $mutability)? Ty<'tcx>,
+                context: TyContext,
+            ) {
+                if self.visits_types() {
+                    self.visit_ty(ty, context);
+                }
+            }
+
+            /// Calls `visit_args` unless `visits_types` returns false, see `maybe_visit_ty`.
+            fn maybe_visit_args(
+                &mut self,
+                args: & $($mutability)? GenericArgsRef<'tcx>,
+                location: Location,
+            ) {
+                if self.visits_types() {
+                    self.visit_args(args, location);
+                }
+            }
+
             fn visit_basic_block_data(
                 &mut self,
                 block: BasicBlock,
@@ -95,6 +207,16 @@ fn visit_source_scope_data(
                 self.super_source_scope_data(scope_data);
             }
 
+            /// Called for the callee and call site of every source scope that was introduced
+            /// by inlining.
+            fn visit_inlined_call(
+                &mut self,
+                callee: & $($mutability)? ty::Instance<'tcx>,
+                callsite_span: $(& $mutability)? Span,
+            ) {
+                self.super_inlined_call(callee, callsite_span);
+            }
+
             fn visit_statement(
                 &mut self,
                 statement: & $($mutability)? Statement<'tcx>,
@@ -120,6 +242,17 @@ fn visit_terminator(
                 self.super_terminator(terminator, location);
             }
 
+            /// Called for the callee and arguments of every `TailCall` terminator. Tail calls
+            /// have no destination, since they never return to the caller.
+            fn visit_tail_call(
+                &mut self,
+                func: & $($mutability)? Operand<'tcx>,
+                args: & $($mutability)? [Spanned<Operand<'tcx>>],
+                location: Location,
+            ) {
+                self.super_tail_call(func, args, location);
+            }
+
             fn visit_assert_message(
                 &mut self,
                 msg: & $($mutability)? AssertMessage<'tcx>,
@@ -128,6 +261,54 @@ fn visit_assert_message(
                 self.super_assert_message(msg, location);
             }
 
+            /// Called for the local of every `StorageDead` statement, which ends the storage of
+            /// the local: any reference to it that is still used afterwards is dangling.
+            fn visit_storage_dead(
+                &mut self,
+                local: $(& $mutability)? Local,
+                location: Location,
+            ) {
+                self.super_storage_dead(local, location);
+            }
+
+            /// Called for the dropped place of every `Drop` terminator. `replace` is true if the
+            /// drop is of the old value of a place that is being assigned to.
+            ///
+            /// All `Drop` terminators are inserted by the compiler, at the end of a scope or
+            /// before an assignment. A user-written `drop(x)` is not a `Drop` terminator but a
+            /// `Call` to `core::mem::drop` that moves `x`, so it is not visited by this method.
+            fn visit_drop(
+                &mut self,
+                place: & $($mutability)? Place<'tcx>,
+                replace: bool,
+                location: Location,
+            ) {
+                self.super_drop(place, replace, location);
+            }
+
+            /// Called for the unwind action of every terminator that has one: `Drop`, `Call`,
+            /// `Assert`, `InlineAsm` and `FalseUnwind`. Passes that need to remap cleanup
+            /// blocks can override this instead of matching on every terminator kind.
+            fn visit_unwind_action(
+                &mut self,
+                unwind: & $($mutability)? UnwindAction,
+                location: Location,
+            ) {
+                self.super_unwind_action(unwind, location);
+            }
+
+            /// Called for the targets of every `SwitchInt` terminator, after its discriminant.
+            /// `SwitchTargets::iter` gives the value of each arm with the block it jumps to, and
+            /// `SwitchTargets::otherwise` the block taken when no value matches, so match-arm
+            /// analyses can read them without matching on the terminator kind.
+            fn visit_switch_targets(
+                &mut self,
+                targets: & $($mutability)? SwitchTargets,
+                location: Location,
+            ) {
+                self.super_switch_targets(targets, location);
+            }
+
             fn visit_rvalue(
                 &mut self,
                 rvalue: & $($mutability)? Rvalue<'tcx>,
@@ -136,6 +317,17 @@ fn visit_rvalue(
                 self.super_rvalue(rvalue, location);
             }
 
+            /// Called for the place of every `Discriminant` rvalue, i.e. the enum (or
+            /// coroutine) whose discriminant is read. The type being read is the type of
+            /// `place`, see `Place::ty`.
+            fn visit_discriminant_read(
+                &mut self,
+                place: & $($mutability)? Place<'tcx>,
+                location: Location,
+            ) {
+                self.super_discriminant_read(place, location);
+            }
+
             fn visit_operand(
                 &mut self,
                 operand: & $($mutability)? Operand<'tcx>,
@@ -154,6 +346,17 @@ fn visit_ascribe_user_ty(
                 self.super_ascribe_user_ty(place, variance, user_ty, location);
             }
 
+            /// Called for the options and per-line spans of every `InlineAsm` terminator, before
+            /// its operands are visited.
+            fn visit_inline_asm_options(
+                &mut self,
+                options: & $($mutability)? InlineAsmOptions,
+                line_spans: & $($mutability)? &'tcx [Span],
+                location: Location,
+            ) {
+                self.super_inline_asm_options(options, line_spans, location);
+            }
+
             fn visit_coverage(
                 &mut self,
                 kind: & $($mutability)? coverage::CoverageKind,
@@ -192,6 +395,36 @@ fn visit_const_operand(
                 self.super_const_operand(constant, location);
             }
 
+            /// Called for the value of every evaluated constant operand, i.e. `Const::Val`.
+            /// Matching on the `ConstValue` tells how the value is represented: an immediate
+            /// `Scalar`, a `ZeroSized` value, a `Slice` (a wide pointer to memory), or an
+            /// `Indirect` value stored in an allocation, which is how large constants like
+            /// arrays and structs with many fields are stored.
+            fn visit_const_value(
+                &mut self,
+                value: $( & $mutability)? ConstValue<'tcx>,
+                location: Location,
+            ) {
+                self.super_const_value(value, location);
+            }
+
+            /// Called by `super_const_value` for every allocation that an evaluated constant
+            /// points to: the target of a pointer `Scalar`, and the target of every pointer
+            /// (relocation) stored in the memory of a `Slice`. An `Indirect` value is stored in
+            /// an allocation rather than pointing to one, but that allocation is passed too,
+            /// since it is just as much a dependency of the constant.
+            ///
+            /// Only the allocations referenced by the constant itself are passed: reading the
+            /// relocations of those allocations needs a `TyCtxt`, see
+            /// `const_allocs::ConstAllocs` for a visitor that collects them recursively.
+            fn visit_const_alloc_relocation(
+                &mut self,
+                _alloc_id: AllocId,
+                _location: Location,
+            ) {}
+
+            visit_nullary_op_fns!($($mutability)?);
+
             fn visit_ty_const(
                 &mut self,
                 ct: $( & $mutability)? ty::Const<'tcx>,
@@ -214,6 +447,19 @@ fn visit_source_info(
                 self.super_source_info(source_info);
             }
 
+            /// Called for the `fn_span` of `Call` and `TailCall` terminators, which spans the
+            /// callee and its arguments, e.g. `foo(a, b)` in `let x = foo(a, b);`, unlike the
+            /// span of the terminator's source info. By default, this is not passed to
+            /// `visit_span`, so visitors that rewrite spans keep the `fn_span` unchanged unless
+            /// they override this method.
+            fn visit_fn_span(
+                &mut self,
+                fn_span: $(& $mutability)? Span,
+                location: Location,
+            ) {
+                self.super_fn_span(fn_span, location);
+            }
+
             fn visit_ty(
                 &mut self,
                 ty: $(& $mutability)? Ty<'tcx>,
@@ -229,6 +475,16 @@ fn visit_user_type_projection(
                 self.super_user_type_projection(ty);
             }
 
+            /// Called for every reference to an entry of `Body::user_type_annotations`: in user
+            /// type projections, which are used by type ascription statements and local
+            /// declarations, in constant operands, and in ADT aggregates. This is not called for
+            /// the entries of the table themselves, see `visit_user_type_annotation`. A pass that
+            /// removes entries from the table can use a `MutVisitor` to remap the indices.
+            fn visit_user_type_annotation_index(
+                &mut self,
+                _index: $(& $mutability)? UserTypeAnnotationIndex,
+            ) {}
+
             fn visit_user_type_annotation(
                 &mut self,
                 index: UserTypeAnnotationIndex,
@@ -245,6 +501,9 @@ fn visit_region(
                 self.super_region(region);
             }
 
+            /// Generic arguments are not destructured: unless `visits_const_arg_types` opts in,
+            /// the types they contain are never passed to `visit_ty`, so every type that
+            /// `visit_ty` sees appears directly in the MIR.
             fn visit_args(
                 &mut self,
                 args: & $($mutability)? GenericArgsRef<'tcx>,
@@ -261,6 +520,15 @@ fn visit_local_decl(
                 self.super_local_decl(local, local_decl);
             }
 
+            /// Called by `super_local_decl` with the origin of the local: whether it is a user
+            /// variable, a temporary, or some other kind of local. Bodies loaded from other
+            /// crates don't have this information, so this isn't called for their locals.
+            fn visit_local_info(
+                &mut self,
+                _local: Local,
+                _local_info: &LocalInfo<'tcx>,
+            ) {}
+
             fn visit_var_debug_info(
                 &mut self,
                 var_debug_info: & $($mutability)* VarDebugInfo<'tcx>,
@@ -275,6 +543,59 @@ fn visit_local(
                 _location: Location,
             ) {}
 
+            /// Called for every `ConstantIndex` projection, with its offset, minimum length and
+            /// whether the offset is counted from the end of the array or slice.
+            ///
+            /// The projected element is at index `offset` if `from_end` is false, and at index
+            /// `len - offset` otherwise. `min_length` is the length that the array or slice is
+            /// assumed to have at least: it is the exact length when indexing an array, and the
+            /// minimum length required by the slice pattern when indexing a slice.
+            ///
+            /// This hook doesn't know which place is projected, see `visit_projection_elem`
+            /// for that.
+            fn visit_constant_index(
+                &mut self,
+                _offset: u64,
+                _min_length: u64,
+                _from_end: bool,
+                _location: Location,
+            ) {}
+
+            /// Called for every `Subslice` projection, with its bounds. The subslice starts at
+            /// index `from`, and ends at index `to` if `from_end` is false, or at index
+            /// `len - to` otherwise, see `ProjectionElem::Subslice`.
+            fn visit_subslice(
+                &mut self,
+                _from: u64,
+                _to: u64,
+                _from_end: bool,
+                _location: Location,
+            ) {}
+
+            /// Called for every `ConstantIndex` and `Subslice` projection, after
+            /// `visit_constant_index` or `visit_subslice`. These are the two projections whose
+            /// indices can be counted from the end of the array or slice, so analyses that
+            /// handle `from_end` the same way for both can override this single hook instead.
+            fn visit_from_end_projection(
+                &mut self,
+                _elem: PlaceElem<'tcx>,
+                _location: Location,
+            ) {}
+
+            /// Called for every `Downcast` projection, with the name and the index of the
+            /// variant that the place is viewed as. The projection doesn't record the enum
+            /// or coroutine itself: it is the type of the projected place, which is available
+            /// to `visit_projection_elem`.
+            fn visit_downcast(
+                &mut self,
+                _name: Option<Symbol>,
+                _variant_index: VariantIdx,
+                _location: Location,
+            ) {}
+
+            /// Called once for every `Return` terminator, before the return place is visited.
+            fn visit_return(&mut self, _location: Location) {}
+
             fn visit_source_scope(
                 &mut self,
                 scope: $(& $mutability)? SourceScope,
@@ -331,44 +652,52 @@ fn super_source_scope_data(
                     self.visit_source_scope($(& $mutability)? *parent_scope);
                 }
                 if let Some((callee, callsite_span)) = inlined {
-                    let location = Location::START;
-
-                    self.visit_span($(& $mutability)? *callsite_span);
-
-                    let ty::Instance { def: callee_def, args: callee_args } = callee;
-                    match callee_def {
-                        ty::InstanceKind::Item(_def_id) => {}
-
-                        ty::InstanceKind::Intrinsic(_def_id) |
-                        ty::InstanceKind::VTableShim(_def_id) |
-                        ty::InstanceKind::ReifyShim(_def_id, _) |
-                        ty::InstanceKind::Virtual(_def_id, _) |
-                        ty::InstanceKind::ThreadLocalShim(_def_id) |
-                        ty::InstanceKind::ClosureOnceShim { call_once: _def_id, track_caller: _ } |
-                        ty::InstanceKind::ConstructCoroutineInClosureShim {
-                            coroutine_closure_def_id: _def_id,
-                            receiver_by_ref: _,
-                        } |
-                        ty::InstanceKind::CoroutineKindShim { coroutine_def_id: _def_id } |
-                        ty::InstanceKind::AsyncDropGlueCtorShim(_def_id, None) |
-                        ty::InstanceKind::DropGlue(_def_id, None) => {}
-
-                        ty::InstanceKind::FnPtrShim(_def_id, ty) |
-                        ty::InstanceKind::DropGlue(_def_id, Some(ty)) |
-                        ty::InstanceKind::CloneShim(_def_id, ty) |
-                        ty::InstanceKind::FnPtrAddrShim(_def_id, ty) |
-                        ty::InstanceKind::AsyncDropGlueCtorShim(_def_id, Some(ty)) => {
-                            // FIXME(eddyb) use a better `TyContext` here.
-                            self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
-                        }
-                    }
-                    self.visit_args(callee_args, location);
+                    self.visit_inlined_call(callee, $(& $mutability)? *callsite_span);
                 }
                 if let Some(inlined_parent_scope) = inlined_parent_scope {
                     self.visit_source_scope($(& $mutability)? *inlined_parent_scope);
                 }
             }
 
+            fn super_inlined_call(
+                &mut self,
+                callee: & $($mutability)? ty::Instance<'tcx>,
+                callsite_span: $(& $mutability)? Span,
+            ) {
+                let location = Location::START;
+
+                self.visit_span(callsite_span);
+
+                let ty::Instance { def: callee_def, args: callee_args } = callee;
+                match callee_def {
+                    ty::InstanceKind::Item(_def_id) => {}
+
+                    ty::InstanceKind::Intrinsic(_def_id) |
+                    ty::InstanceKind::VTableShim(_def_id) |
+                    ty::InstanceKind::ReifyShim(_def_id, _) |
+                    ty::InstanceKind::Virtual(_def_id, _) |
+                    ty::InstanceKind::ThreadLocalShim(_def_id) |
+                    ty::InstanceKind::ClosureOnceShim { call_once: _def_id, track_caller: _ } |
+                    ty::InstanceKind::ConstructCoroutineInClosureShim {
+                        coroutine_closure_def_id: _def_id,
+                        receiver_by_ref: _,
+                    } |
+                    ty::InstanceKind::CoroutineKindShim { coroutine_def_id: _def_id } |
+                    ty::InstanceKind::AsyncDropGlueCtorShim(_def_id, None) |
+                    ty::InstanceKind::DropGlue(_def_id, None) => {}
+
+                    ty::InstanceKind::FnPtrShim(_def_id, ty) |
+                    ty::InstanceKind::DropGlue(_def_id, Some(ty)) |
+                    ty::InstanceKind::CloneShim(_def_id, ty) |
+                    ty::InstanceKind::FnPtrAddrShim(_def_id, ty) |
+                    ty::InstanceKind::AsyncDropGlueCtorShim(_def_id, Some(ty)) => {
+                        // FIXME(eddyb) use a better `TyContext` here.
+                        self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                    }
+                }
+                self.maybe_visit_args(callee_args, location);
+            }
+
             fn super_statement(&mut self,
                                statement: & $($mutability)? Statement<'tcx>,
                                location: Location) {
@@ -413,11 +742,7 @@ fn super_statement(&mut self,
                         );
                     }
                     StatementKind::StorageDead(local) => {
-                        self.visit_local(
-                            $(& $mutability)? *local,
-                            PlaceContext::NonUse(NonUseContext::StorageDead),
-                            location
-                        );
+                        self.visit_storage_dead($(& $mutability)? *local, location);
                     }
                     StatementKind::Retag(kind, place) => {
                         self.visit_retag($(& $mutability)? *kind, place, location);
@@ -480,10 +805,18 @@ fn super_terminator(&mut self,
                     TerminatorKind::UnwindTerminate(_) |
                     TerminatorKind::CoroutineDrop |
                     TerminatorKind::Unreachable |
-                    TerminatorKind::FalseEdge { .. } |
-                    TerminatorKind::FalseUnwind { .. } => {}
+                    TerminatorKind::FalseEdge { .. } => {}
+
+                    TerminatorKind::FalseUnwind {
+                        real_target: _,
+                        unwind,
+                    } => {
+                        self.visit_unwind_action(unwind, location);
+                    }
 
                     TerminatorKind::Return => {
+                        self.visit_return(location);
+
                         // `return` logically moves from the return place `_0`. Note that the place
                         // cannot be changed by any visitor, though.
                         let $($mutability)? local = RETURN_PLACE;
@@ -502,22 +835,20 @@ fn super_terminator(&mut self,
 
                     TerminatorKind::SwitchInt {
                         discr,
-                        targets: _
+                        targets
                     } => {
                         self.visit_operand(discr, location);
+                        self.visit_switch_targets(targets, location);
                     }
 
                     TerminatorKind::Drop {
                         place,
                         target: _,
-                        unwind: _,
-                        replace: _,
+                        unwind,
+                        replace,
                     } => {
-                        self.visit_place(
-                            place,
-                            PlaceContext::MutatingUse(MutatingUseContext::Drop),
-                            location
-                        );
+                        self.visit_drop(place, *replace, location);
+                        self.visit_unwind_action(unwind, location);
                     }
 
                     TerminatorKind::Call {
@@ -525,10 +856,11 @@ fn super_terminator(&mut self,
                         args,
                         destination,
                         target: _,
-                        unwind: _,
+                        unwind,
                         call_source: _,
-                        fn_span: _
+                        fn_span
                     } => {
+                        self.visit_fn_span($(& $mutability)? *fn_span, location);
                         self.visit_operand(func, location);
                         for arg in args {
                             self.visit_operand(&$($mutability)? arg.node, location);
@@ -538,17 +870,16 @@ fn super_terminator(&mut self,
                             PlaceContext::MutatingUse(MutatingUseContext::Call),
                             location
                         );
+                        self.visit_unwind_action(unwind, location);
                     }
 
                     TerminatorKind::TailCall {
                         func,
                         args,
-                        fn_span: _,
+                        fn_span,
                     } => {
-                        self.visit_operand(func, location);
-                        for arg in args {
-                            self.visit_operand(&$($mutability)? arg.node, location);
-                        }
+                        self.visit_fn_span($(& $mutability)? *fn_span, location);
+                        self.visit_tail_call(func, args, location);
                     },
 
                     TerminatorKind::Assert {
@@ -556,10 +887,11 @@ fn super_terminator(&mut self,
                         expected: _,
                         msg,
                         target: _,
-                        unwind: _,
+                        unwind,
                     } => {
                         self.visit_operand(cond, location);
                         self.visit_assert_message(msg, location);
+                        self.visit_unwind_action(unwind, location);
                     }
 
                     TerminatorKind::Yield {
@@ -579,11 +911,12 @@ fn super_terminator(&mut self,
                     TerminatorKind::InlineAsm {
                         template: _,
                         operands,
-                        options: _,
-                        line_spans: _,
+                        options,
+                        line_spans,
                         targets: _,
-                        unwind: _,
+                        unwind,
                     } => {
+                        self.visit_inline_asm_options(options, line_spans, location);
                         for op in operands {
                             match op {
                                 InlineAsmOperand::In { value, .. } => {
@@ -615,10 +948,31 @@ fn super_terminator(&mut self,
                                 | InlineAsmOperand::Label { target_index: _ } => {}
                             }
                         }
+                        self.visit_unwind_action(unwind, location);
                     }
                 }
             }
 
+            fn super_tail_call(&mut self,
+                               func: & $($mutability)? Operand<'tcx>,
+                               args: & $($mutability)? [Spanned<Operand<'tcx>>],
+                               location: Location) {
+                self.visit_operand(func, location);
+                for arg in args {
+                    self.visit_operand(&$($mutability)? arg.node, location);
+                }
+            }
+
+            fn super_discriminant_read(&mut self,
+                                       place: & $($mutability)? Place<'tcx>,
+                                       location: Location) {
+                self.visit_place(
+                    place,
+                    PlaceContext::NonMutatingUse(NonMutatingUseContext::Inspect),
+                    location
+                );
+            }
+
             fn super_assert_message(&mut self,
                                     msg: & $($mutability)? AssertMessage<'tcx>,
                                     location: Location) {
@@ -645,6 +999,37 @@ fn super_assert_message(&mut self,
                 }
             }
 
+            fn super_storage_dead(&mut self,
+                                  local: $(& $mutability)? Local,
+                                  location: Location) {
+                self.visit_local(
+                    local,
+                    PlaceContext::NonUse(NonUseContext::StorageDead),
+                    location
+                );
+            }
+
+            fn super_drop(&mut self,
+                          place: & $($mutability)? Place<'tcx>,
+                          _replace: bool,
+                          location: Location) {
+                self.visit_place(
+                    place,
+                    PlaceContext::MutatingUse(MutatingUseContext::Drop),
+                    location
+                );
+            }
+
+            fn super_unwind_action(&mut self,
+                                   _unwind: & $($mutability)? UnwindAction,
+                                   _location: Location) {
+            }
+
+            fn super_switch_targets(&mut self,
+                                    _targets: & $($mutability)? SwitchTargets,
+                                    _location: Location) {
+            }
+
             fn super_rvalue(&mut self,
                             rvalue: & $($mutability)? Rvalue<'tcx>,
                             location: Location) {
@@ -677,7 +1062,7 @@ fn super_rvalue(&mut self,
                     Rvalue::CopyForDeref(place) => {
                         self.visit_place(
                             place,
-                            PlaceContext::NonMutatingUse(NonMutatingUseContext::Inspect),
+                            PlaceContext::NonMutatingUse(NonMutatingUseContext::CopyForDeref),
                             location
                         );
                     }
@@ -704,7 +1089,7 @@ fn super_rvalue(&mut self,
 
                     Rvalue::Cast(_cast_kind, operand, ty) => {
                         self.visit_operand(operand, location);
-                        self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                        self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
                     }
 
                     Rvalue::BinaryOp(_bin_op, box(lhs, rhs)) => {
@@ -717,22 +1102,18 @@ fn super_rvalue(&mut self,
                     }
 
                     Rvalue::Discriminant(place) => {
-                        self.visit_place(
-                            place,
-                            PlaceContext::NonMutatingUse(NonMutatingUseContext::Inspect),
-                            location
-                        );
+                        self.visit_discriminant_read(place, location);
                     }
 
-                    Rvalue::NullaryOp(_op, ty) => {
-                        self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                    Rvalue::NullaryOp(op, ty) => {
+                        visit_nullary_op!(self, rvalue, op, ty, location, $($mutability)?);
                     }
 
                     Rvalue::Aggregate(kind, operands) => {
                         let kind = &$($mutability)? **kind;
                         match kind {
                             AggregateKind::Array(ty) => {
-                                self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                                self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
                             }
                             AggregateKind::Tuple => {
                             }
@@ -740,31 +1121,36 @@ fn super_rvalue(&mut self,
                                 _adt_def,
                                 _variant_index,
                                 args,
-                                _user_args,
+                                user_args,
                                 _active_field_index
                             ) => {
-                                self.visit_args(args, location);
+                                self.maybe_visit_args(args, location);
+                                if let Some(user_args) = user_args {
+                                    self.visit_user_type_annotation_index(
+                                        $(& $mutability)? *user_args
+                                    );
+                                }
                             }
                             AggregateKind::Closure(
                                 _,
                                 closure_args
                             ) => {
-                                self.visit_args(closure_args, location);
+                                self.maybe_visit_args(closure_args, location);
                             }
                             AggregateKind::Coroutine(
                                 _,
                                 coroutine_args,
                             ) => {
-                                self.visit_args(coroutine_args, location);
+                                self.maybe_visit_args(coroutine_args, location);
                             }
                             AggregateKind::CoroutineClosure(
                                 _,
                                 coroutine_closure_args,
                             ) => {
-                                self.visit_args(coroutine_closure_args, location);
+                                self.maybe_visit_args(coroutine_closure_args, location);
                             }
                             AggregateKind::RawPtr(ty, _) => {
-                                self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                                self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
                             }
                         }
 
@@ -775,7 +1161,7 @@ fn super_rvalue(&mut self,
 
                     Rvalue::ShallowInitBox(operand, ty) => {
                         self.visit_operand(operand, location);
-                        self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                        self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
                     }
                 }
             }
@@ -817,6 +1203,12 @@ fn super_ascribe_user_ty(&mut self,
                 self.visit_user_type_projection(user_ty);
             }
 
+            fn super_inline_asm_options(&mut self,
+                                        _options: & $($mutability)? InlineAsmOptions,
+                                        _line_spans: & $($mutability)? &'tcx [Span],
+                                        _location: Location) {
+            }
+
             fn super_coverage(&mut self,
                               _kind: & $($mutability)? coverage::CoverageKind,
                               _location: Location) {
@@ -841,10 +1233,14 @@ fn super_local_decl(&mut self,
                     ty,
                     user_ty,
                     source_info,
-                    local_info: _,
+                    local_info,
                 } = local_decl;
 
-                self.visit_ty($(& $mutability)? *ty, TyContext::LocalDecl {
+                if let ClearCrossCrate::Set(local_info) = local_info {
+                    self.visit_local_info(local, local_info);
+                }
+
+                self.maybe_visit_ty($(& $mutability)? *ty, TyContext::LocalDecl {
                     local,
                     source_info: *source_info,
                 });
@@ -871,10 +1267,10 @@ fn super_var_debug_info(
                 self.visit_source_info(source_info);
                 let location = Location::START;
                 if let Some(box VarDebugInfoFragment { ref $($mutability)? ty, ref $($mutability)? projection }) = composite {
-                    self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                    self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
                     for elem in projection {
                         let ProjectionElem::Field(_, ty) = elem else { bug!() };
-                        self.visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                        self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
                     }
                 }
                 match value {
@@ -900,18 +1296,65 @@ fn super_const_operand(
             ) {
                 let ConstOperand {
                     span,
-                    user_ty: _, // no visit method for this
+                    user_ty,
                     const_,
                 } = constant;
 
                 self.visit_span($(& $mutability)? *span);
+                if let Some(user_ty) = user_ty {
+                    self.visit_user_type_annotation_index($(& $mutability)? *user_ty);
+                }
                 match const_ {
                     Const::Ty(_, ct) => self.visit_ty_const($(&$mutability)? *ct, location),
-                    Const::Val(_, ty) => self.visit_ty($(& $mutability)? *ty, TyContext::Location(location)),
-                    Const::Unevaluated(_, ty) => self.visit_ty($(& $mutability)? *ty, TyContext::Location(location)),
+                    Const::Val(value, ty) => {
+                        self.visit_const_value($(& $mutability)? *value, location);
+                        self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                    }
+                    Const::Unevaluated(uv, ty) => {
+                        self.maybe_visit_ty($(& $mutability)? *ty, TyContext::Location(location));
+                        if self.visits_types() && self.visits_const_arg_types() {
+                            visit_unevaluated_args!(self, uv, location, $($mutability)?);
+                        }
+                    }
+                }
+            }
+
+            fn super_const_value(
+                &mut self,
+                value: $(& $mutability)? ConstValue<'tcx>,
+                location: Location,
+            ) {
+                // The value is only read, whether it is visited by value or by reference.
+                let value: &ConstValue<'tcx> = &value;
+                match *value {
+                    ConstValue::Scalar(Scalar::Ptr(ptr, _)) => {
+                        self.visit_const_alloc_relocation(ptr.provenance.alloc_id(), location);
+                    }
+                    ConstValue::Slice { data, meta: _ } => {
+                        for &(_, provenance) in data.inner().provenance().ptrs().iter() {
+                            self.visit_const_alloc_relocation(provenance.alloc_id(), location);
+                        }
+                    }
+                    ConstValue::Indirect { alloc_id, offset: _ } => {
+                        self.visit_const_alloc_relocation(alloc_id, location);
+                    }
+                    ConstValue::Scalar(Scalar::Int(_)) | ConstValue::ZeroSized => {}
                 }
             }
 
+            fn super_nullary_op(
+                &mut self,
+                op: & $($mutability)? NullOp<'tcx>,
+                ty: $(& $mutability)? Ty<'tcx>,
+                location: Location,
+            ) {
+                debug_assert!(
+                    !matches!(op, NullOp::UbChecks) || ty.is_bool(),
+                    "`UbChecks` applied to non-`bool` type {ty:?}",
+                );
+                self.maybe_visit_ty(ty, TyContext::Location(location));
+            }
+
             fn super_ty_const(
                 &mut self,
                 _ct: $(& $mutability)? ty::Const<'tcx>,
@@ -923,6 +1366,9 @@ fn super_ty_const(
             fn super_span(&mut self, _span: $(& $mutability)? Span) {
             }
 
+            fn super_fn_span(&mut self, _fn_span: $(& $mutability)? Span, _location: Location) {
+            }
+
             fn super_source_info(&mut self, source_info: & $($mutability)? SourceInfo) {
                 let SourceInfo {
                     span,
@@ -935,8 +1381,10 @@ fn super_source_info(&mut self, source_info: & $($mutability)? SourceInfo) {
 
             fn super_user_type_projection(
                 &mut self,
-                _ty: & $($mutability)? UserTypeProjection,
+                ty: & $($mutability)? UserTypeProjection,
             ) {
+                let UserTypeProjection { base, projs: _ } = ty;
+                self.visit_user_type_annotation_index($(& $mutability)? *base);
             }
 
             fn super_user_type_annotation(
@@ -945,7 +1393,7 @@ fn super_user_type_annotation(
                 ty: & $($mutability)? CanonicalUserTypeAnnotation<'tcx>,
             ) {
                 self.visit_span($(& $mutability)? ty.span);
-                self.visit_ty($(& $mutability)? ty.inferred_ty, TyContext::UserTy(ty.span));
+                self.maybe_visit_ty($(& $mutability)? ty.inferred_ty, TyContext::UserTy(ty.span));
             }
 
             fn super_ty(&mut self, _ty: $(& $mutability)? Ty<'tcx>) {
@@ -1000,6 +1448,33 @@ macro_rules! basic_blocks_iter {
     };
 }
 
+macro_rules! visit_unevaluated_args {
+    ($self:ident, $uv:ident, $location:ident, mut) => {
+        let mut changed = false;
+        let new_args: smallvec::SmallVec<[_; 8]> = $uv
+            .args
+            .iter()
+            .map(|arg| match arg.unpack() {
+                crate::ty::GenericArgKind::Type(ty) => {
+                    let mut new_ty = ty;
+                    $self.visit_ty(&mut new_ty, TyContext::ConstArg($location));
+                    changed |= new_ty != ty;
+                    new_ty.into()
+                }
+                _ => arg,
+            })
+            .collect();
+        if changed {
+            $uv.args = $self.tcx().mk_args(&new_args);
+        }
+    };
+    ($self:ident, $uv:ident, $location:ident,) => {
+        for ty in $uv.args.types() {
+            $self.visit_ty(ty, TyContext::ConstArg($location));
+        }
+    };
+}
+
 macro_rules! extra_body_methods {
     (mut) => {
         fn visit_body_preserves_cfg(&mut self, body: &mut Body<'tcx>) {
@@ -1015,16 +1490,20 @@ fn super_body_preserves_cfg(&mut self, body: &mut Body<'tcx>) {
 
 macro_rules! super_body {
     ($self:ident, $body:ident, $($mutability:ident, $invalidate:tt)?) => {
+        $self.visit_body_start($body);
+        $self.visit_phase($body.phase);
+
         let span = $body.span;
         if let Some(gen) = &$($mutability)? $body.coroutine {
+            $self.visit_coroutine_kind(gen.coroutine_kind);
             if let Some(yield_ty) = $(& $mutability)? gen.yield_ty {
-                $self.visit_ty(
+                $self.maybe_visit_ty(
                     yield_ty,
                     TyContext::YieldTy(SourceInfo::outermost(span))
                 );
             }
             if let Some(resume_ty) = $(& $mutability)? gen.resume_ty {
-                $self.visit_ty(
+                $self.maybe_visit_ty(
                     resume_ty,
                     TyContext::ResumeTy(SourceInfo::outermost(span))
                 );
@@ -1039,13 +1518,20 @@ macro_rules! super_body {
             $self.visit_source_scope_data(scope);
         }
 
-        $self.visit_ty(
+        $self.maybe_visit_ty(
             $(& $mutability)? $body.return_ty(),
             TyContext::ReturnTy(SourceInfo::outermost($body.span))
         );
 
-        for local in $body.local_decls.indices() {
-            $self.visit_local_decl(local, & $($mutability)? $body.local_decls[local]);
+        let local_order = $self.local_decl_order();
+        if local_order == LocalOrder::Index {
+            for local in $body.local_decls.indices() {
+                $self.visit_local_decl(local, & $($mutability)? $body.local_decls[local]);
+            }
+        } else {
+            for local in local_order.locals(&$body.local_decls) {
+                $self.visit_local_decl(local, & $($mutability)? $body.local_decls[local]);
+            }
         }
 
         #[allow(unused_macro_rules)]
@@ -1070,9 +1556,56 @@ macro_rules! type_annotations {
             let location = Location::START;
             $self.visit_const_operand(const_, location);
         }
+
+        if let Some(coverage_info) = &$($mutability)? $body.function_coverage_info {
+            $self.visit_coverage_info(coverage_info);
+        }
+
+        $self.visit_body_end($body);
     }
 }
 
+macro_rules! visit_nullary_op_fns {
+    (mut) => {
+        /// Called for every `Rvalue::NullaryOp`, with its operation and the type it applies to.
+        /// Most nullary operations compute a property of `ty`, like its size, but
+        /// `NullOp::UbChecks` computes whether UB checks are enabled at runtime, and its type
+        /// is always `bool`.
+        ///
+        /// Returning `Some(operand)` replaces the whole rvalue with `Rvalue::Use(operand)`, e.g.
+        /// `const true` to force the UB checks on. The operand is not visited.
+        fn visit_nullary_op(
+            &mut self,
+            op: &mut NullOp<'tcx>,
+            ty: &mut Ty<'tcx>,
+            location: Location,
+        ) -> Option<Operand<'tcx>> {
+            self.super_nullary_op(op, ty, location);
+            None
+        }
+    };
+    () => {
+        /// Called for every `Rvalue::NullaryOp`, with its operation and the type it applies to.
+        /// Most nullary operations compute a property of `ty`, like its size, but
+        /// `NullOp::UbChecks` computes whether UB checks are enabled at runtime, and its type
+        /// is always `bool`.
+        fn visit_nullary_op(&mut self, op: &NullOp<'tcx>, ty: Ty<'tcx>, location: Location) {
+            self.super_nullary_op(op, ty, location);
+        }
+    };
+}
+
+macro_rules! visit_nullary_op {
+    ($self:ident, $rvalue:ident, $op:ident, $ty:ident, $location:ident, mut) => {
+        if let Some(operand) = $self.visit_nullary_op($op, $ty, $location) {
+            *$rvalue = Rvalue::Use(operand);
+        }
+    };
+    ($self:ident, $rvalue:ident, $op:ident, $ty:ident, $location:ident,) => {
+        $self.visit_nullary_op($op, *$ty, $location)
+    };
+}
+
 macro_rules! visit_place_fns {
     (mut) => {
         fn tcx<'a>(&'a self) -> TyCtxt<'tcx>;
@@ -1132,23 +1665,34 @@ fn process_projection_elem(
                 }
                 PlaceElem::Field(field, ty) => {
                     let mut new_ty = ty;
-                    self.visit_ty(&mut new_ty, TyContext::Location(location));
+                    self.maybe_visit_ty(&mut new_ty, TyContext::Location(location));
                     if ty != new_ty { Some(PlaceElem::Field(field, new_ty)) } else { None }
                 }
                 PlaceElem::OpaqueCast(ty) => {
                     let mut new_ty = ty;
-                    self.visit_ty(&mut new_ty, TyContext::Location(location));
+                    self.maybe_visit_ty(&mut new_ty, TyContext::Location(location));
                     if ty != new_ty { Some(PlaceElem::OpaqueCast(new_ty)) } else { None }
                 }
                 PlaceElem::Subtype(ty) => {
                     let mut new_ty = ty;
-                    self.visit_ty(&mut new_ty, TyContext::Location(location));
+                    self.maybe_visit_ty(&mut new_ty, TyContext::Location(location));
                     if ty != new_ty { Some(PlaceElem::Subtype(new_ty)) } else { None }
                 }
-                PlaceElem::Deref
-                | PlaceElem::ConstantIndex { .. }
-                | PlaceElem::Subslice { .. }
-                | PlaceElem::Downcast(..) => None,
+                PlaceElem::ConstantIndex { offset, min_length, from_end } => {
+                    self.visit_constant_index(offset, min_length, from_end, location);
+                    self.visit_from_end_projection(elem, location);
+                    None
+                }
+                PlaceElem::Subslice { from, to, from_end } => {
+                    self.visit_subslice(from, to, from_end, location);
+                    self.visit_from_end_projection(elem, location);
+                    None
+                }
+                PlaceElem::Downcast(name, variant_index) => {
+                    self.visit_downcast(name, variant_index, location);
+                    None
+                }
+                PlaceElem::Deref => None,
             }
         }
     };
@@ -1214,7 +1758,7 @@ fn super_projection_elem(
                 ProjectionElem::OpaqueCast(ty)
                 | ProjectionElem::Subtype(ty)
                 | ProjectionElem::Field(_, ty) => {
-                    self.visit_ty(ty, TyContext::Location(location));
+                    self.maybe_visit_ty(ty, TyContext::Location(location));
                 }
                 ProjectionElem::Index(local) => {
                     self.visit_local(
@@ -1223,10 +1767,18 @@ fn super_projection_elem(
                         location,
                     );
                 }
-                ProjectionElem::Deref
-                | ProjectionElem::Subslice { from: _, to: _, from_end: _ }
-                | ProjectionElem::ConstantIndex { offset: _, min_length: _, from_end: _ }
-                | ProjectionElem::Downcast(_, _) => {}
+                ProjectionElem::ConstantIndex { offset, min_length, from_end } => {
+                    self.visit_constant_index(offset, min_length, from_end, location);
+                    self.visit_from_end_projection(elem, location);
+                }
+                ProjectionElem::Subslice { from, to, from_end } => {
+                    self.visit_subslice(from, to, from_end, location);
+                    self.visit_from_end_projection(elem, location);
+                }
+                ProjectionElem::Downcast(name, variant_index) => {
+                    self.visit_downcast(name, variant_index, location);
+                }
+                ProjectionElem::Deref => {}
             }
         }
     };
@@ -1257,8 +1809,75 @@ fn apply(&self, location: Location, visitor: &mut dyn Visitor<'tcx>) {
     }
 }
 
+/// The order in which `super_body` visits local declarations, see `Visitor::local_decl_order`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LocalOrder {
+    /// In index order, starting with the return place.
+    Index,
+    /// Locals whose type is contained in the type of another local are visited before it,
+    /// e.g. a local of type `T` before a local of type `Vec<T>`: this is a topological order of
+    /// the containment relation between the types of the locals. Among the locals whose
+    /// contained types have all been visited, the one with the smallest index goes first.
+    Dependency,
+}
+
+impl LocalOrder {
+    /// Returns the locals of `local_decls` in this order.
+    pub fn locals(self, local_decls: &LocalDecls<'_>) -> Vec<Local> {
+        match self {
+            LocalOrder::Index => local_decls.indices().collect(),
+            LocalOrder::Dependency => {
+                // The types that the type of each local strictly contains.
+                let contained: IndexVec<Local, FxHashSet<Ty<'_>>> = local_decls
+                    .iter()
+                    .map(|decl| {
+                        let tys = decl.ty.walk().filter_map(|arg| arg.as_type());
+                        tys.filter(|&ty| ty != decl.ty).collect()
+                    })
+                    .collect();
+
+                // A local depends on every local whose type its own type strictly contains.
+                // Strict containment has no cycles, so every local is eventually ready.
+                let mut dependents = IndexVec::from_elem(Vec::new(), local_decls);
+                let mut dependencies = IndexVec::from_elem(0, local_decls);
+                for (local, decl) in local_decls.iter_enumerated() {
+                    for (other, contained) in contained.iter_enumerated() {
+                        if contained.contains(&decl.ty) {
+                            dependents[local].push(other);
+                            dependencies[other] += 1;
+                        }
+                    }
+                }
+
+                let mut ready: BinaryHeap<Reverse<Local>> = dependencies
+                    .iter_enumerated()
+                    .filter(|&(_, &count)| count == 0)
+                    .map(|(local, _)| Reverse(local))
+                    .collect();
+                let mut locals = Vec::with_capacity(local_decls.len());
+                while let Some(Reverse(local)) = ready.pop() {
+                    locals.push(local);
+                    for &dependent in &dependents[local] {
+                        dependencies[dependent] -= 1;
+                        if dependencies[dependent] == 0 {
+                            ready.push(Reverse(dependent));
+                        }
+                    }
+                }
+                locals
+            }
+        }
+    }
+}
+
 /// Extra information passed to `visit_ty` and friends to give context
 /// about where the type etc appears.
+///
+/// Since the visitor does not recurse into types or generic arguments, a type is
+/// only ever visited at its top-level position: types nested inside generic
+/// arguments are only reachable through `visit_args`, except for the type arguments of
+/// unevaluated constants, which are passed as `TyContext::ConstArg` to the visitors that opt in
+/// with `visits_const_arg_types`.
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum TyContext {
     LocalDecl {
@@ -1281,6 +1900,10 @@ pub enum TyContext {
 
     /// A type found at some location.
     Location(Location),
+
+    /// A type argument of an unevaluated constant used as an operand at some location, e.g.
+    /// `T` in `<T as Trait>::CONST`. Only used if `visits_const_arg_types` returns true.
+    ConstArg(Location),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -1291,6 +1914,13 @@ pub enum NonMutatingUseContext {
     Copy,
     /// Consumed as part of an operand.
     Move,
+    /// Read by `Rvalue::CopyForDeref`.
+    ///
+    /// The copied value is a pointer that will only ever be used as the base of a `Deref`
+    /// projection, so this read never lets the pointer itself escape. Note that reading a
+    /// local as the base of a `Deref` projection within the same place (e.g. `_1` in
+    /// `(*_1).0`) is reported as `Projection` instead.
+    CopyForDeref,
     /// Shared borrow.
     SharedBorrow,
     /// A fake borrow.
@@ -1389,6 +2019,13 @@ pub fn is_address_of(&self) -> bool {
         )
     }
 
+    /// Returns `true` if this place context represents a read whose result will only be used
+    /// as the base of a `Deref` projection, i.e. the place is read by `Rvalue::CopyForDeref`.
+    #[inline]
+    pub fn is_deref_bound_read(&self) -> bool {
+        matches!(self, PlaceContext::NonMutatingUse(NonMutatingUseContext::CopyForDeref))
+    }
+
     /// Returns `true` if this place context represents a storage live or storage dead marker.
     #[inline]
     pub fn is_storage_marker(&self) -> bool {
@@ -1422,3 +2059,6 @@ pub fn is_place_assignment(&self) -> bool {
         )
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,516 @@
+use std::num::NonZero;
+
+use rustc_index::IndexVec;
+use rustc_span::source_map::Spanned;
+use rustc_span::DUMMY_SP;
+use rustc_target::abi::Size;
+
+use super::*;
+use crate::mir::interpret::Pointer;
+use crate::mir::leaf_body::is_leaf_body;
+use crate::mir::location_map::{renumber_locations, LocationMap};
+use crate::mir::max_call_arity::MaxCallArity;
+use crate::mir::post_divergence::PostDivergenceChecker;
+use crate::mir::predecessor_counts::predecessor_counts;
+use crate::mir::statement_data_flow::StatementDataFlow;
+use crate::mir::unwind_edges::UnwindEdgeChecker;
+use crate::ty::TyCtxt;
+
+/// Records every local visited, in visiting order.
+struct VisitedLocals(Vec<Local>);
+
+impl<'tcx> Visitor<'tcx> for VisitedLocals {
+    fn visit_local(&mut self, local: Local, _: PlaceContext, _: Location) {
+        self.0.push(local);
+    }
+}
+
+fn block<'tcx>(
+    statements: Vec<StatementKind<'tcx>>,
+    terminator: TerminatorKind<'tcx>,
+) -> BasicBlockData<'tcx> {
+    let source_info = SourceInfo::outermost(DUMMY_SP);
+    BasicBlockData {
+        statements: statements.into_iter().map(|kind| Statement { source_info, kind }).collect(),
+        terminator: Some(Terminator { source_info, kind: terminator }),
+        is_cleanup: false,
+    }
+}
+
+fn local_order<'tcx>(
+    statements: Vec<StatementKind<'tcx>>,
+    terminator: TerminatorKind<'tcx>,
+) -> Vec<Local> {
+    let body = Body::new_cfg_only(IndexVec::from_raw(vec![block(statements, terminator)]));
+
+    // `visit_body` would also visit the local declarations, which this body doesn't have.
+    let mut visitor = VisitedLocals(Vec::new());
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        visitor.visit_basic_block_data(block, data);
+    }
+    visitor.0
+}
+
+fn place<'tcx>(local: u32) -> Place<'tcx> {
+    Place::from(Local::from_u32(local))
+}
+
+fn locals(locals: &[u32]) -> Vec<Local> {
+    locals.iter().copied().map(Local::from_u32).collect()
+}
+
+#[test]
+fn assign_visits_destination_before_rvalue() {
+    let assign = StatementKind::Assign(Box::new((
+        place(1),
+        Rvalue::BinaryOp(BinOp::Add, Box::new((Operand::Copy(place(2)), Operand::Move(place(3))))),
+    )));
+    assert_eq!(local_order(vec![assign], TerminatorKind::Unreachable), locals(&[1, 2, 3]));
+}
+
+#[test]
+fn call_visits_callee_then_args_then_destination() {
+    let call = TerminatorKind::Call {
+        func: Operand::Copy(place(1)),
+        args: [
+            Spanned { node: Operand::Move(place(2)), span: DUMMY_SP },
+            Spanned { node: Operand::Copy(place(3)), span: DUMMY_SP },
+        ]
+        .into(),
+        destination: place(4),
+        target: None,
+        unwind: UnwindAction::Unreachable,
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    };
+    assert_eq!(local_order(vec![], call), locals(&[1, 2, 3, 4]));
+}
+
+#[test]
+fn switch_int_visits_discriminant() {
+    let switch = TerminatorKind::SwitchInt {
+        discr: Operand::Copy(place(1)),
+        targets: SwitchTargets::static_if(0, START_BLOCK, START_BLOCK),
+    };
+    assert_eq!(local_order(vec![], switch), locals(&[1]));
+}
+
+#[test]
+fn aggregate_visits_operands_left_to_right() {
+    let aggregate = StatementKind::Assign(Box::new((
+        place(1),
+        Rvalue::Aggregate(
+            Box::new(AggregateKind::Tuple),
+            [Operand::Copy(place(2)), Operand::Move(place(3)), Operand::Copy(place(4))]
+                .into_iter()
+                .collect(),
+        ),
+    )));
+    assert_eq!(local_order(vec![aggregate], TerminatorKind::Unreachable), locals(&[1, 2, 3, 4]));
+}
+
+/// Records every unwind action visited.
+struct UnwindActions(Vec<UnwindAction>);
+
+impl<'tcx> Visitor<'tcx> for UnwindActions {
+    fn visit_unwind_action(&mut self, unwind: &UnwindAction, _: Location) {
+        self.0.push(*unwind);
+    }
+}
+
+#[test]
+fn unwind_actions_are_visited() {
+    let cleanup = BasicBlock::from_u32(1);
+    let terminators = [
+        TerminatorKind::Drop {
+            place: place(1),
+            target: START_BLOCK,
+            unwind: UnwindAction::Cleanup(cleanup),
+            replace: false,
+        },
+        TerminatorKind::FalseUnwind { real_target: START_BLOCK, unwind: UnwindAction::Continue },
+        TerminatorKind::Goto { target: START_BLOCK },
+    ];
+    let mut visitor = UnwindActions(Vec::new());
+    for kind in terminators {
+        let terminator = Terminator { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+        visitor.visit_terminator(&terminator, START_BLOCK.start_location());
+    }
+    assert_eq!(visitor.0, [UnwindAction::Cleanup(cleanup), UnwindAction::Continue]);
+}
+
+/// Records every dropped local, with whether the drop is part of an assignment.
+struct Drops(Vec<(Local, bool)>);
+
+impl<'tcx> Visitor<'tcx> for Drops {
+    fn visit_drop(&mut self, place: &Place<'tcx>, replace: bool, _: Location) {
+        self.0.push((place.local, replace));
+    }
+}
+
+#[test]
+fn drop_terminators_are_visited_with_replace_flag() {
+    let mut visitor = Drops(Vec::new());
+    for (local, replace) in [(1, false), (2, true)] {
+        let kind = TerminatorKind::Drop {
+            place: place(local),
+            target: START_BLOCK,
+            unwind: UnwindAction::Continue,
+            replace,
+        };
+        let terminator = Terminator { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+        visitor.visit_terminator(&terminator, START_BLOCK.start_location());
+    }
+    assert_eq!(visitor.0, [(Local::from_u32(1), false), (Local::from_u32(2), true)]);
+}
+
+/// Records every user type annotation index visited, in visiting order.
+struct UserTypeAnnotationIndices(Vec<UserTypeAnnotationIndex>);
+
+impl<'tcx> Visitor<'tcx> for UserTypeAnnotationIndices {
+    fn visit_user_type_annotation_index(&mut self, index: UserTypeAnnotationIndex) {
+        self.0.push(index);
+    }
+}
+
+#[test]
+fn ascriptions_visit_user_type_annotation_index() {
+    let mut visitor = UserTypeAnnotationIndices(Vec::new());
+    let projection =
+        UserTypeProjection { base: UserTypeAnnotationIndex::from_u32(3), projs: vec![] };
+    let kind =
+        StatementKind::AscribeUserType(Box::new((place(1), projection)), ty::Variance::Covariant);
+    let statement = Statement { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+    visitor.visit_statement(&statement, START_BLOCK.start_location());
+    assert_eq!(visitor.0, [UserTypeAnnotationIndex::from_u32(3)]);
+}
+
+/// Records the location of every `fn_span` visited.
+struct FnSpans(Vec<Location>);
+
+impl<'tcx> Visitor<'tcx> for FnSpans {
+    fn visit_fn_span(&mut self, _: Span, location: Location) {
+        self.0.push(location);
+    }
+}
+
+#[test]
+fn calls_visit_fn_span() {
+    let mut visitor = FnSpans(Vec::new());
+    let kind = TerminatorKind::Call {
+        func: Operand::Copy(place(1)),
+        args: [].into(),
+        destination: place(2),
+        target: None,
+        unwind: UnwindAction::Unreachable,
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    };
+    let terminator = Terminator { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+    visitor.visit_terminator(&terminator, START_BLOCK.start_location());
+    assert_eq!(visitor.0, [START_BLOCK.start_location()]);
+}
+
+/// Counts the spans passed to `visit_span`.
+struct SpanCount(usize);
+
+impl<'tcx> Visitor<'tcx> for SpanCount {
+    fn visit_span(&mut self, _: Span) {
+        self.0 += 1;
+    }
+}
+
+#[test]
+fn fn_span_is_not_visited_as_span_by_default() {
+    let mut visitor = SpanCount(0);
+    let kind = TerminatorKind::Call {
+        func: Operand::Copy(place(1)),
+        args: [].into(),
+        destination: place(2),
+        target: None,
+        unwind: UnwindAction::Unreachable,
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    };
+    let terminator = Terminator { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+    visitor.visit_terminator(&terminator, START_BLOCK.start_location());
+    // Only the span of the terminator's source info.
+    assert_eq!(visitor.0, 1);
+}
+
+/// Records the arms and the otherwise target of every `SwitchInt`.
+struct SwitchArms(Vec<(Vec<(u128, BasicBlock)>, BasicBlock)>);
+
+impl<'tcx> Visitor<'tcx> for SwitchArms {
+    fn visit_switch_targets(&mut self, targets: &SwitchTargets, _: Location) {
+        self.0.push((targets.iter().collect(), targets.otherwise()));
+    }
+}
+
+#[test]
+fn switch_int_visits_switch_targets() {
+    let [bb1, bb2, bb3] = [1, 2, 3].map(BasicBlock::from_u32);
+    let mut visitor = SwitchArms(Vec::new());
+    let kind = TerminatorKind::SwitchInt {
+        discr: Operand::Copy(place(1)),
+        targets: SwitchTargets::new([(0, bb1), (7, bb2)].into_iter(), bb3),
+    };
+    let terminator = Terminator { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+    visitor.visit_terminator(&terminator, START_BLOCK.start_location());
+    assert_eq!(visitor.0, [(vec![(0, bb1), (7, bb2)], bb3)]);
+}
+
+/// Records the allocations that visited constants refer to.
+struct ConstAllocIds(Vec<AllocId>);
+
+impl<'tcx> Visitor<'tcx> for ConstAllocIds {
+    fn visit_const_alloc_relocation(&mut self, alloc_id: AllocId, _: Location) {
+        self.0.push(alloc_id);
+    }
+}
+
+#[test]
+fn const_values_visit_const_alloc_relocation() {
+    let [target, storage] = [7, 9].map(|id| AllocId(NonZero::new(id).unwrap()));
+    let location = START_BLOCK.start_location();
+    let mut visitor = ConstAllocIds(Vec::new());
+    let ptr = Pointer::new(target.into(), Size::ZERO);
+    visitor.visit_const_value(ConstValue::Scalar(Scalar::Ptr(ptr, 8)), location);
+    visitor.visit_const_value(ConstValue::Scalar(Scalar::from_u32(7)), location);
+    visitor.visit_const_value(
+        ConstValue::Indirect { alloc_id: storage, offset: Size::ZERO },
+        location,
+    );
+    visitor.visit_const_value(ConstValue::ZeroSized, location);
+    assert_eq!(visitor.0, [target, storage]);
+}
+
+/// Marks every inline assembly block as `nostack`.
+struct AddNoStack;
+
+impl<'tcx> MutVisitor<'tcx> for AddNoStack {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        unreachable!("no place is projected")
+    }
+
+    fn visit_inline_asm_options(
+        &mut self,
+        options: &mut InlineAsmOptions,
+        _: &mut &'tcx [Span],
+        _: Location,
+    ) {
+        options.insert(InlineAsmOptions::NOSTACK);
+    }
+}
+
+#[test]
+fn mut_visitor_edits_inline_asm_options() {
+    let kind = TerminatorKind::InlineAsm {
+        template: &[],
+        operands: Box::new([]),
+        options: InlineAsmOptions::PURE | InlineAsmOptions::NOMEM,
+        line_spans: &[],
+        targets: Box::new([START_BLOCK]),
+        unwind: UnwindAction::Unreachable,
+    };
+    let mut terminator = Terminator { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+    AddNoStack.visit_terminator(&mut terminator, START_BLOCK.start_location());
+    let TerminatorKind::InlineAsm { options, .. } = terminator.kind else { unreachable!() };
+    assert_eq!(
+        options,
+        InlineAsmOptions::PURE | InlineAsmOptions::NOMEM | InlineAsmOptions::NOSTACK
+    );
+}
+
+#[test]
+fn location_map_follows_inserts_and_removes() {
+    let bb1 = BasicBlock::from_u32(1);
+    let blocks = vec![
+        block(vec![StatementKind::Nop; 3], TerminatorKind::Goto { target: bb1 }),
+        block(vec![StatementKind::Nop], TerminatorKind::Return),
+    ];
+    let body = Body::new_cfg_only(IndexVec::from_raw(blocks));
+    let at = |block, statement_index| Location { block, statement_index };
+
+    let mut map = LocationMap::new(&body);
+    // Insert a statement before `bb0[1]`, then remove the original `bb0[2]`, now at `bb0[3]`.
+    map.record_insert(at(START_BLOCK, 1));
+    map.record_remove(at(START_BLOCK, 3));
+
+    assert_eq!(map.map_location(at(START_BLOCK, 0)), Some(at(START_BLOCK, 0)));
+    assert_eq!(map.map_location(at(START_BLOCK, 1)), Some(at(START_BLOCK, 2)));
+    assert_eq!(map.map_location(at(START_BLOCK, 2)), None);
+    assert_eq!(map.map_location(body.terminator_loc(START_BLOCK)), Some(at(START_BLOCK, 3)));
+    assert_eq!(map.map_location(at(bb1, 0)), Some(at(bb1, 0)));
+
+    // The edited body has as many statements as the original one.
+    let mut locations = vec![at(START_BLOCK, 2), at(START_BLOCK, 1), at(bb1, 1)];
+    renumber_locations(&body, &map, &mut locations);
+    assert_eq!(locations, [at(START_BLOCK, 2), at(bb1, 1)]);
+}
+
+#[test]
+fn statement_data_flow_of_assignments_and_storage_markers() {
+    let location = START_BLOCK.start_location();
+    let data_flow = |kind| {
+        let statement = Statement { source_info: SourceInfo::outermost(DUMMY_SP), kind };
+        let (defs, uses) = StatementDataFlow::compute(&statement, location);
+        (defs.to_vec(), uses.to_vec())
+    };
+
+    let add = StatementKind::Assign(Box::new((
+        place(1),
+        Rvalue::BinaryOp(BinOp::Add, Box::new((Operand::Copy(place(1)), Operand::Copy(place(2))))),
+    )));
+    assert_eq!(data_flow(add), (locals(&[1]), locals(&[1, 2])));
+
+    let copy_twice = StatementKind::Assign(Box::new((
+        place(3),
+        Rvalue::BinaryOp(BinOp::Mul, Box::new((Operand::Copy(place(4)), Operand::Move(place(4))))),
+    )));
+    assert_eq!(data_flow(copy_twice), (locals(&[3]), locals(&[4])));
+
+    let storage_live = StatementKind::StorageLive(Local::from_u32(5));
+    assert_eq!(data_flow(storage_live), (vec![], vec![]));
+}
+
+#[test]
+fn predecessor_counts_count_edges_and_optionally_unwind_edges() {
+    let [bb1, bb2, bb3] = [1, 2, 3].map(BasicBlock::from_u32);
+    let switch = TerminatorKind::SwitchInt {
+        discr: Operand::Copy(place(1)),
+        targets: SwitchTargets::new([(0, bb1), (1, bb1)].into_iter(), bb2),
+    };
+    let call = TerminatorKind::Call {
+        func: Operand::Copy(place(2)),
+        args: [].into(),
+        destination: place(3),
+        target: Some(bb2),
+        unwind: UnwindAction::Cleanup(bb3),
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    };
+    let mut cleanup = block(vec![], TerminatorKind::UnwindResume);
+    cleanup.is_cleanup = true;
+    let blocks = vec![
+        block(vec![], switch),
+        block(vec![], call),
+        block(vec![], TerminatorKind::Return),
+        cleanup,
+    ];
+    let body = Body::new_cfg_only(IndexVec::from_raw(blocks));
+
+    assert_eq!(predecessor_counts(&body, true).raw, [0, 2, 2, 1]);
+    assert_eq!(predecessor_counts(&body, false).raw, [0, 2, 2, 0]);
+}
+
+#[test]
+fn leaf_bodies_make_no_calls() {
+    let bb1 = BasicBlock::from_u32(1);
+    let body_with = |kind| {
+        let blocks = vec![block(vec![], kind), block(vec![], TerminatorKind::Return)];
+        Body::new_cfg_only(IndexVec::from_raw(blocks))
+    };
+
+    let goto = body_with(TerminatorKind::Goto { target: bb1 });
+    assert!(is_leaf_body(&goto, true));
+
+    let drop = body_with(TerminatorKind::Drop {
+        place: place(1),
+        target: bb1,
+        unwind: UnwindAction::Continue,
+        replace: false,
+    });
+    assert!(is_leaf_body(&drop, false));
+    assert!(!is_leaf_body(&drop, true));
+
+    let call = body_with(TerminatorKind::Call {
+        func: Operand::Copy(place(1)),
+        args: [].into(),
+        destination: place(2),
+        target: Some(bb1),
+        unwind: UnwindAction::Continue,
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    });
+    assert!(!is_leaf_body(&call, false));
+}
+
+#[test]
+fn unwind_edges_must_lead_to_cleanup_blocks() {
+    let [bb1, bb2, bb3] = [1, 2, 3].map(BasicBlock::from_u32);
+    let drop = TerminatorKind::Drop {
+        place: place(1),
+        target: bb1,
+        unwind: UnwindAction::Cleanup(bb2),
+        replace: false,
+    };
+    let call = TerminatorKind::Call {
+        func: Operand::Copy(place(2)),
+        args: [].into(),
+        destination: place(3),
+        target: Some(bb2),
+        unwind: UnwindAction::Cleanup(bb3),
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    };
+    let mut cleanup = block(vec![], TerminatorKind::UnwindResume);
+    cleanup.is_cleanup = true;
+    let blocks = vec![
+        block(vec![], drop),
+        block(vec![], call),
+        block(vec![], TerminatorKind::Return),
+        cleanup,
+    ];
+    let body = Body::new_cfg_only(IndexVec::from_raw(blocks));
+
+    // `bb2` is not a cleanup block, `bb3` is.
+    assert_eq!(UnwindEdgeChecker::check(&body), [(body.terminator_loc(START_BLOCK), bb2)]);
+}
+
+#[test]
+fn max_call_arity_looks_at_calls_and_tail_calls() {
+    let bb1 = BasicBlock::from_u32(1);
+    let args = |locals: &[u32]| -> Box<[_]> {
+        locals
+            .iter()
+            .map(|&local| Spanned { node: Operand::Copy(place(local)), span: DUMMY_SP })
+            .collect()
+    };
+    let call = TerminatorKind::Call {
+        func: Operand::Copy(place(1)),
+        args: args(&[2, 3]),
+        destination: place(4),
+        target: Some(bb1),
+        unwind: UnwindAction::Continue,
+        call_source: CallSource::Misc,
+        fn_span: DUMMY_SP,
+    };
+    let tail_call = TerminatorKind::TailCall {
+        func: Operand::Copy(place(1)),
+        args: args(&[2, 3, 4]),
+        fn_span: DUMMY_SP,
+    };
+    let blocks = vec![block(vec![], call), block(vec![], tail_call)];
+    assert_eq!(MaxCallArity::compute(&Body::new_cfg_only(IndexVec::from_raw(blocks))), 3);
+
+    let blocks = vec![block(vec![], TerminatorKind::Return)];
+    assert_eq!(MaxCallArity::compute(&Body::new_cfg_only(IndexVec::from_raw(blocks))), 0);
+}
+
+#[test]
+fn post_divergence_statements_precede_unreachable() {
+    let bb1 = BasicBlock::from_u32(1);
+    let copy = |to, from| {
+        StatementKind::Assign(Box::new((place(to), Rvalue::Use(Operand::Copy(place(from))))))
+    };
+    let blocks = vec![
+        block(vec![copy(1, 2)], TerminatorKind::Goto { target: bb1 }),
+        block(
+            vec![StatementKind::StorageLive(Local::from_u32(3)), copy(3, 1), StatementKind::Nop],
+            TerminatorKind::Unreachable,
+        ),
+    ];
+    let body = Body::new_cfg_only(IndexVec::from_raw(blocks));
+    assert_eq!(PostDivergenceChecker::check(&body), [Location { block: bb1, statement_index: 1 }]);
+}
@@ -0,0 +1,54 @@
+//! Finds borrows of locals that are held across a `Yield` in a movable coroutine.
+
+use rustc_hir::Movability;
+
+use crate::mir::cross_yield::CrossYieldLocals;
+use crate::mir::visit::Visitor;
+use crate::mir::{Body, Local, Location, Place, Rvalue};
+
+/// Reports the borrows of a local of a movable coroutine whose reference is held across a
+/// yield. Once the coroutine yields, it may be moved, which would leave such a reference
+/// dangling, so borrowck rejects them. Static coroutines, like the ones of `async` blocks,
+/// can't be moved once resumed, so they are never reported.
+///
+/// This is a best-effort syntactic check meant to catch lowering bugs, not a replacement for
+/// borrowck: a borrow is reported if the local that the reference is assigned to is used
+/// across a yield, as computed by [`CrossYieldLocals`], which over-approximates liveness, and
+/// the reference is not tracked once it is copied to another local. Borrows of places behind
+/// a dereference, like the upvars of the coroutine, don't point into the coroutine and are
+/// not reported.
+pub struct YieldBorrowChecker {
+    /// The local that each borrow is assigned to, with the location of the borrow.
+    borrows: Vec<(Local, Location)>,
+}
+
+impl YieldBorrowChecker {
+    /// Returns the locations of the borrows of `body` that are held across a yield.
+    pub fn check<'tcx>(body: &Body<'tcx>) -> Vec<Location> {
+        let Some(coroutine_kind) = body.coroutine_kind() else { return Vec::new() };
+        if coroutine_kind.movability() == Movability::Static {
+            return Vec::new();
+        }
+
+        let mut visitor = YieldBorrowChecker { borrows: Vec::new() };
+        visitor.visit_body(body);
+        let cross_yield = CrossYieldLocals::compute(body);
+        visitor
+            .borrows
+            .into_iter()
+            .filter(|&(reference, _)| cross_yield.contains(reference))
+            .map(|(_, location)| location)
+            .collect()
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for YieldBorrowChecker {
+    fn visit_assign(&mut self, dest: &Place<'tcx>, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Ref(_, _, borrowed) = rvalue
+            && !borrowed.is_indirect()
+        {
+            self.borrows.push((dest.local, location));
+        }
+        self.super_assign(dest, rvalue, location);
+    }
+}
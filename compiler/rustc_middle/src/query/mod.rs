@@ -504,6 +504,22 @@
         desc { |tcx| "building MIR for `{}`", tcx.def_path_str(key) }
     }
 
+    /// Build the by-move body of a coroutine returned by a coroutine-closure, which is used
+    /// for its `FnOnce`/`AsyncFnOnce` shim. Returns `None` for any other body, and for the
+    /// coroutines of `FnOnce` coroutine-closures, which are already by-move.
+    ///
+    /// This is computed from the result of `mir_built`, so it must be forced before that is
+    /// stolen.
+    ///
+    /// This is not cached on disk, so it doesn't add any reuse across incremental sessions: it
+    /// is recomputed whenever `mir_promoted` is, which rebuilds `mir_built` anyway. The by-move
+    /// body is only reused as part of the `optimized_mir` of the by-ref body, which it is
+    /// attached to by the `ByMoveBody` pass, like before it was built by this query.
+    query coroutine_by_move_body(key: LocalDefId) -> &'tcx Option<mir::Body<'tcx>> {
+        arena_cache
+        desc { |tcx| "building the by-move body of `{}`", tcx.def_path_str(key) }
+    }
+
     /// Try to build an abstract representation of the given constant.
     query thir_abstract_const(
         key: DefId
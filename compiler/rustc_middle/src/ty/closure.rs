@@ -419,6 +419,7 @@ pub fn to_mutbl_lossy(self) -> hir::Mutability {
 }
 
 pub fn analyze_coroutine_closure_captures<'a, 'tcx: 'a, T>(
+    tcx: TyCtxt<'tcx>,
     parent_captures: impl IntoIterator<Item = &'a CapturedPlace<'tcx>>,
     child_captures: impl IntoIterator<Item = &'a CapturedPlace<'tcx>>,
     mut for_each: impl FnMut((usize, &'a CapturedPlace<'tcx>), (usize, &'a CapturedPlace<'tcx>)) -> T,
@@ -461,11 +462,23 @@ pub fn analyze_coroutine_closure_captures<'a, 'tcx: 'a, T>(
                     field_used_at_least_once = true;
                 }
 
-                // Make sure the field was used at least once.
-                assert!(
-                    field_used_at_least_once,
-                    "we captured {parent_capture:#?} but it was not used in the child coroutine?"
-                );
+                // Make sure the field was used at least once. If we already emitted an error,
+                // the child may have dropped a capture that the parent still has, so don't ICE
+                // during error recovery.
+                if !field_used_at_least_once && tcx.dcx().has_errors().is_some() {
+                    tcx.dcx().span_delayed_bug(
+                        parent_capture.get_path_span(tcx),
+                        format!(
+                            "we captured {parent_capture:#?} but it was not used in the child \
+                            coroutine?"
+                        ),
+                    );
+                } else {
+                    assert!(
+                        field_used_at_least_once,
+                        "we captured {parent_capture:#?} but it was not used in the child coroutine?"
+                    );
+                }
             }
             assert_eq!(child_captures.next(), None, "leftover child captures?");
         },
@@ -198,6 +198,7 @@ fn for_place(place: Place<'_>, context: PlaceContext) -> Option<DefUse> {
                 NonMutatingUseContext::AddressOf
                 | NonMutatingUseContext::Copy
                 | NonMutatingUseContext::Inspect
+                | NonMutatingUseContext::CopyForDeref
                 | NonMutatingUseContext::Move
                 | NonMutatingUseContext::PlaceMention
                 | NonMutatingUseContext::FakeBorrow
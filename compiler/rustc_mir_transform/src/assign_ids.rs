@@ -0,0 +1,68 @@
+//! Tags every assignment of a body with a sequential id recorded in its debuginfo. This is not
+//! a MIR pass, since the ids must be handed back to the caller, but it can be used directly by
+//! external drivers, e.g. to correlate execution traces with the statements of a body.
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+/// Records a debuginfo entry named `__assign_N`, whose value is the constant `N: usize`, for
+/// every `Assign` statement of a body.
+///
+/// Ids are handed out in the order of the `MutVisitor` traversal: blocks in index order, and
+/// the statements of each block in order. They continue from `next_id`, so that the ids of
+/// several bodies annotated with the same annotator don't overlap. The entries use the source
+/// info of their statement, so they are in the scope of the assignment.
+///
+/// The entries are constants, so `-Zvalidate-mir` accepts them like any other debuginfo, and
+/// no statement of the body is added or modified. Since the ids are not stored in the
+/// statements themselves, the locations that `annotate` returns are only valid until the body
+/// is next transformed.
+pub struct AssignIdAnnotator<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The id of the next assignment.
+    pub next_id: u64,
+    annotations: Vec<(u64, Location, SourceInfo)>,
+}
+
+impl<'tcx> AssignIdAnnotator<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, first_id: u64) -> Self {
+        AssignIdAnnotator { tcx, next_id: first_id, annotations: Vec::new() }
+    }
+
+    /// Tags the assignments of `body`, and returns the id of each of them with its location.
+    pub fn annotate(&mut self, body: &mut Body<'tcx>) -> Vec<(u64, Location)> {
+        self.visit_body(body);
+        self.annotations
+            .drain(..)
+            .map(|(id, location, source_info)| {
+                body.var_debug_info.push(VarDebugInfo {
+                    name: Symbol::intern(&format!("__assign_{id}")),
+                    source_info,
+                    composite: None,
+                    value: VarDebugInfoContents::Const(ConstOperand {
+                        span: source_info.span,
+                        user_ty: None,
+                        const_: Const::from_usize(self.tcx, id),
+                    }),
+                    argument_index: None,
+                });
+                (id, location)
+            })
+            .collect()
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for AssignIdAnnotator<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Location) {
+        if let StatementKind::Assign(_) = statement.kind {
+            self.annotations.push((self.next_id, location, statement.source_info));
+            self.next_id += 1;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! This pass makes the copies of a local that is itself a copy of another local copy that other
+//! local instead. It is off by default, but can be enabled on the command line
+//! (`-Zmir-enable-passes=+CopyCoalescer`).
+//!
+//! Unlike `CopyProp`, which replaces all the locals of a copy class by a single one everywhere,
+//! this only rewrites `Operand::Copy`s: the intermediate locals keep their assignments, their
+//! borrows and their debuginfo, and locals that are moved are left alone. It runs before GVN
+//! and the first `SimplifyLocals`, so that the intermediate assignments that become unused can be
+//! removed by them, whereas `CopyProp` only runs once most optimizations are done.
+
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+use rustc_middle::mir::visit::{MutVisitor, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+use crate::ssa::SsaLocals;
+
+pub struct CopyCoalescer;
+
+impl<'tcx> MirPass<'tcx> for CopyCoalescer {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running CopyCoalescer on {:?}", body.source);
+        let mut rewriter = CopyRewriter::new(tcx, body);
+        rewriter.visit_body_preserves_cfg(body);
+    }
+}
+
+/// Rewrites every `copy _b`, where `_b` is only assigned once, by `_b = copy _a`, and `_a` is
+/// only assigned once too, to `copy _a`. Chains like `_b = copy _a; _c = copy _b` are followed
+/// to their start, so `copy _c` becomes `copy _a`.
+///
+/// The single-assignment locals are the ones of `SsaLocals`: their value never changes once
+/// assigned, and their assignment dominates their uses, so `_a` has the same value as `_b`
+/// wherever `_b` is used. Locals whose only assignment is not a plain copy of a local, e.g. a
+/// move, a copy of a projection or any other rvalue, start their own chain. A local that is
+/// moved anywhere in the body doesn't start a chain either, since it may have been moved out
+/// of when `_b` is copied.
+///
+/// The storage markers of the locals that start a chain are removed, since their new uses may
+/// be out of their original storage range.
+struct CopyRewriter<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// For every local, the local that its copies should copy instead.
+    canonical: IndexVec<Local, Local>,
+    storage_to_remove: BitSet<Local>,
+}
+
+impl<'tcx> CopyRewriter<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Self {
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let ssa = SsaLocals::new(tcx, body, param_env);
+        let mut moved = MovedLocals(BitSet::new_empty(body.local_decls.len()));
+        moved.visit_body(body);
+
+        let mut canonical: IndexVec<Local, Local> = body.local_decls.indices().collect();
+        let mut storage_to_remove = BitSet::new_empty(body.local_decls.len());
+        // The assignments are in reverse postorder, so the source of a copy is seen first.
+        for (local, rvalue, _) in ssa.assignments(body) {
+            if let Rvalue::Use(Operand::Copy(place)) = rvalue
+                && let Some(source) = place.as_local()
+                && ssa.is_ssa(source)
+                && !moved.0.contains(source)
+            {
+                canonical[local] = canonical[source];
+                storage_to_remove.insert(canonical[source]);
+            }
+        }
+        CopyRewriter { tcx, canonical, storage_to_remove }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for CopyRewriter<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        if let Operand::Copy(place) = operand {
+            place.local = self.canonical[place.local];
+        }
+        self.super_operand(operand, location);
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Location) {
+        if let StatementKind::StorageLive(local) | StatementKind::StorageDead(local) =
+            statement.kind
+            && self.storage_to_remove.contains(local)
+        {
+            statement.make_nop();
+        } else {
+            self.super_statement(statement, location);
+        }
+    }
+}
+
+/// Collects the locals that are moved out of, directly or through a projection.
+struct MovedLocals(BitSet<Local>);
+
+impl<'tcx> Visitor<'tcx> for MovedLocals {
+    fn visit_operand(&mut self, operand: &Operand<'tcx>, location: Location) {
+        if let Operand::Move(place) = operand
+            && !place.is_indirect()
+        {
+            self.0.insert(place.local);
+        }
+        self.super_operand(operand, location);
+    }
+}
@@ -0,0 +1,32 @@
+//! This pass makes the edges to blocks that only contain a `Goto` go directly to the final target
+//! of the chain of such blocks. It is off by default, but can be enabled on the command line
+//! (`-Zmir-enable-passes=+GotoChainCollapser`).
+//!
+//! `SimplifyCfg` does the same as part of its simplifications, and this pass reuses its logic.
+//! It only redirects edges, and leaves the bypassed blocks, which may become unreachable, to be
+//! removed by the next `SimplifyCfg`.
+//!
+//! Each chain is followed to its end, so a single run reaches the fixpoint. A chain that ends
+//! in a cycle of empty `Goto` blocks, i.e. an empty infinite loop, goes to the first block of the
+//! cycle that it reaches, which then loops on itself. Cleanup blocks can only go to cleanup blocks, and other
+//! blocks to other blocks, so a redirected edge always ends in a block with the same
+//! `is_cleanup` as before.
+
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+use crate::simplify::CfgSimplifier;
+
+pub struct GotoChainCollapser;
+
+impl<'tcx> MirPass<'tcx> for GotoChainCollapser {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, _tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running GotoChainCollapser on {:?}", body.source);
+        CfgSimplifier::new(body).collapse_goto_chains();
+    }
+}
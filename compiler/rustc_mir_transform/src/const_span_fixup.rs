@@ -0,0 +1,58 @@
+//! This pass gives every constant operand the span of the statement or terminator that uses it, so
+//! that constants synthesized by other passes don't point at misleading places in MIR dumps. It is
+//! off by default, but can be enabled on the command line (`-Zmir-enable-passes=+FixupConstSpans`).
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use rustc_span::{Span, DUMMY_SP};
+
+pub struct FixupConstSpans;
+
+impl<'tcx> MirPass<'tcx> for FixupConstSpans {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running FixupConstSpans on {:?}", body.source);
+        let mut visitor = ConstSpanFixup::new(tcx);
+        for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Sets the span of every constant operand to the span of the source info visited last.
+///
+/// Statements and terminators visit their source info before anything else, so when this is
+/// used to visit basic blocks, every constant gets the span of its statement or terminator.
+/// It should not be used to visit a whole body: the local declarations and the debuginfo
+/// have source info too, and `required_consts` are visited last, outside of any statement.
+struct ConstSpanFixup<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    span: Span,
+}
+
+impl<'tcx> ConstSpanFixup<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        ConstSpanFixup { tcx, span: DUMMY_SP }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for ConstSpanFixup<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_source_info(&mut self, source_info: &mut SourceInfo) {
+        self.span = source_info.span;
+        self.super_source_info(source_info);
+    }
+
+    fn visit_const_operand(&mut self, constant: &mut ConstOperand<'tcx>, location: Location) {
+        constant.span = self.span;
+        self.super_const_operand(constant, location);
+    }
+}
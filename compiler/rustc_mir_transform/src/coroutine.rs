@@ -52,6 +52,7 @@
 
 mod by_move_body;
 pub use by_move_body::ByMoveBody;
+pub(crate) use by_move_body::coroutine_by_move_body;
 
 use crate::abort_unwinding_calls;
 use crate::deref_separator::deref_finder;
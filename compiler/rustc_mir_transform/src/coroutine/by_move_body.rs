@@ -1,5 +1,7 @@
-//! This pass constructs a second coroutine body sufficient for return from
+//! This pass attaches a second coroutine body sufficient for return from
 //! `FnOnce`/`AsyncFnOnce` implementations for coroutine-closures (e.g. async closures).
+//! The body itself is constructed by the `coroutine_by_move_body` query, so that it can
+//! be reused across incremental sessions.
 //!
 //! Consider an async closure like:
 //! ```rust
@@ -69,140 +71,292 @@
 //! in case precise captures (edition 2021 closure capture rules) caused the inner coroutine
 //! to split one field capture into two.
 
-use rustc_data_structures::unord::UnordMap;
 use rustc_hir as hir;
-use rustc_middle::bug;
+use rustc_hir::def_id::LocalDefId;
+use rustc_index::IndexVec;
 use rustc_middle::hir::place::{Projection, ProjectionKind};
-use rustc_middle::mir::visit::MutVisitor;
-use rustc_middle::mir::{self, dump_mir, MirPass};
+use rustc_middle::mir::visit::{MutVisitor, Visitor};
+use rustc_middle::mir::{self, dump_mir, traversal, MirPass, PassWhere};
 use rustc_middle::ty::{self, InstanceKind, Ty, TyCtxt, TypeVisitableExt};
+use rustc_middle::{bug, span_bug};
+use rustc_span::Span;
 use rustc_target::abi::{FieldIdx, VariantIdx};
 
+use crate::errors;
+use crate::required_consts::RequiredConstsVisitor;
+
 pub struct ByMoveBody;
 
 impl<'tcx> MirPass<'tcx> for ByMoveBody {
     fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut mir::Body<'tcx>) {
-        // We only need to generate by-move coroutine bodies for coroutines that come
-        // from coroutine-closures.
-        let Some(coroutine_def_id) = body.source.def_id().as_local() else {
-            return;
-        };
-        let Some(hir::CoroutineKind::Desugared(_, hir::CoroutineSource::Closure)) =
-            tcx.coroutine_kind(coroutine_def_id)
-        else {
-            return;
-        };
-
-        // Also, let's skip processing any bodies with errors, since there's no guarantee
-        // the MIR body will be constructed well.
-        let coroutine_ty = body.local_decls[ty::CAPTURE_STRUCT_LOCAL].ty;
-        if coroutine_ty.references_error() {
-            return;
-        }
-
         // We don't need to generate a by-move coroutine if the coroutine body was
         // produced by the `CoroutineKindShim`, since it's already by-move.
         if matches!(body.source.instance, ty::InstanceKind::CoroutineKindShim { .. }) {
             return;
         }
 
-        let ty::Coroutine(_, args) = *coroutine_ty.kind() else { bug!("{body:#?}") };
-        let args = args.as_coroutine();
+        let Some(coroutine_def_id) = body.source.def_id().as_local() else {
+            return;
+        };
+        if let Some(by_move_body) = tcx.coroutine_by_move_body(coroutine_def_id) {
+            // From here on, both bodies go through the same passes, see `run_passes_inner`.
+            debug_assert_eq!(by_move_body.phase, body.phase);
+            body.coroutine.as_mut().unwrap().by_move_body = Some(by_move_body.clone());
+        }
+    }
+}
 
-        let coroutine_kind = args.kind_ty().to_opt_closure_kind().unwrap();
+/// Builds the by-move body of `coroutine_def_id` from its built MIR, see the module docs.
+pub(crate) fn coroutine_by_move_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    coroutine_def_id: LocalDefId,
+) -> Option<mir::Body<'tcx>> {
+    // We only need to generate by-move coroutine bodies for coroutines that come
+    // from coroutine-closures.
+    let Some(hir::CoroutineKind::Desugared(_, hir::CoroutineSource::Closure)) =
+        tcx.coroutine_kind(coroutine_def_id)
+    else {
+        return None;
+    };
 
-        let parent_def_id = tcx.local_parent(coroutine_def_id);
-        let ty::CoroutineClosure(_, parent_args) =
-            *tcx.type_of(parent_def_id).instantiate_identity().kind()
-        else {
-            bug!();
-        };
-        let parent_closure_args = parent_args.as_coroutine_closure();
-        let num_args = parent_closure_args
-            .coroutine_closure_sig()
-            .skip_binder()
-            .tupled_inputs_ty
-            .tuple_fields()
-            .len();
+    let body = tcx.mir_built(coroutine_def_id).borrow();
 
-        let field_remapping: UnordMap<_, _> = ty::analyze_coroutine_closure_captures(
-            tcx.closure_captures(parent_def_id).iter().copied(),
-            tcx.closure_captures(coroutine_def_id).iter().skip(num_args).copied(),
-            |(parent_field_idx, parent_capture), (child_field_idx, child_capture)| {
-                // Store this set of additional projections (fields and derefs).
-                // We need to re-apply them later.
-                let child_precise_captures =
-                    &child_capture.place.projections[parent_capture.place.projections.len()..];
+    // Also, let's skip processing any bodies with errors, since there's no guarantee
+    // the MIR body will be constructed well.
+    let coroutine_ty = body.local_decls[ty::CAPTURE_STRUCT_LOCAL].ty;
+    if coroutine_ty.references_error() {
+        return None;
+    }
 
-                // If the parent captures by-move, and the child captures by-ref, then we
-                // need to peel an additional `deref` off of the body of the child.
-                let needs_deref = child_capture.is_by_ref() && !parent_capture.is_by_ref();
-                if needs_deref {
-                    assert_ne!(
-                        coroutine_kind,
-                        ty::ClosureKind::FnOnce,
-                        "`FnOnce` coroutine-closures return coroutines that capture from \
-                        their body; it will always result in a borrowck error!"
-                    );
-                }
+    let ty::Coroutine(_, args) = *coroutine_ty.kind() else {
+        span_bug!(
+            body.span,
+            "expected the capture struct of `{}` to be a coroutine, found `{coroutine_ty}`",
+            tcx.def_path_str(coroutine_def_id),
+        );
+    };
+    let args = args.as_coroutine();
 
-                // Finally, store the type of the parent's captured place. We need
-                // this when building the field projection in the MIR body later on.
-                let mut parent_capture_ty = parent_capture.place.ty();
-                parent_capture_ty = match parent_capture.info.capture_kind {
-                    ty::UpvarCapture::ByValue => parent_capture_ty,
-                    ty::UpvarCapture::ByRef(kind) => Ty::new_ref(
-                        tcx,
-                        tcx.lifetimes.re_erased,
-                        parent_capture_ty,
-                        kind.to_mutbl_lossy(),
-                    ),
-                };
+    let coroutine_kind = args.kind_ty().to_opt_closure_kind().unwrap();
 
-                (
-                    FieldIdx::from_usize(child_field_idx + num_args),
-                    (
-                        FieldIdx::from_usize(parent_field_idx + num_args),
-                        parent_capture_ty,
-                        needs_deref,
-                        child_precise_captures,
-                    ),
-                )
-            },
-        )
-        .collect();
+    let parent_def_id = tcx.local_parent(coroutine_def_id);
+    let ty::CoroutineClosure(_, parent_args) =
+        *tcx.type_of(parent_def_id).instantiate_identity().kind()
+    else {
+        bug!();
+    };
+    let parent_closure_args = parent_args.as_coroutine_closure();
+    let num_args = parent_closure_args
+        .coroutine_closure_sig()
+        .skip_binder()
+        .tupled_inputs_ty
+        .tuple_fields()
+        .len();
 
-        if coroutine_kind == ty::ClosureKind::FnOnce {
-            assert_eq!(field_remapping.len(), tcx.closure_captures(parent_def_id).len());
-            return;
-        }
+    // The remapping of every field of the child coroutine that is captured from the parent,
+    // indexed by field: it is looked up for every upvar access of the body, and the fields are
+    // dense. The first `num_args` fields are the arguments of the closure, which have none.
+    let mut field_remapping =
+        IndexVec::from_elem_n(None, tcx.closure_captures(coroutine_def_id).len());
+    for (child_field_idx, remapping) in ty::analyze_coroutine_closure_captures(
+        tcx,
+        tcx.closure_captures(parent_def_id).iter().copied(),
+        tcx.closure_captures(coroutine_def_id).iter().skip(num_args).copied(),
+        |(parent_field_idx, parent_capture), (child_field_idx, child_capture)| {
+            // Store this set of additional projections (fields and derefs).
+            // We need to re-apply them later.
+            let child_precise_captures =
+                &child_capture.place.projections[parent_capture.place.projections.len()..];
+
+            // If the parent captures by-move, and the child captures by-ref, then we
+            // need to peel an additional `deref` off of the body of the child. Conversely,
+            // if the parent captures by-ref and the child captures by-move (e.g. the child
+            // copies out of a reference that the parent borrowed), then the upvar we take
+            // from the parent is one layer of ref'ing short, and we must add a `deref`.
+            let deref_adjustment = match (parent_capture.is_by_ref(), child_capture.is_by_ref()) {
+                (false, true) => DerefAdjustment::Peel,
+                (true, false) => DerefAdjustment::Add,
+                (false, false) | (true, true) => DerefAdjustment::None,
+            };
+            if let DerefAdjustment::Peel = deref_adjustment {
+                assert_ne!(
+                    coroutine_kind,
+                    ty::ClosureKind::FnOnce,
+                    "`FnOnce` coroutine-closures return coroutines that capture from \
+                    their body; it will always result in a borrowck error!"
+                );
+            }
+
+            // Store the type of the parent's captured place. We need
+            // this when building the field projection in the MIR body later on.
+            let mut parent_capture_ty = parent_capture.place.ty();
+            parent_capture_ty = match parent_capture.info.capture_kind {
+                ty::UpvarCapture::ByValue => parent_capture_ty,
+                ty::UpvarCapture::ByRef(kind) => Ty::new_ref(
+                    tcx,
+                    tcx.lifetimes.re_erased,
+                    parent_capture_ty,
+                    kind.to_mutbl_lossy(),
+                ),
+            };
+
+            // Finally, store where the parent captured the place, so that MIR dumps of the
+            // by-move body can point at the capture that each of its fields comes from.
+            let parent_capture_span = parent_capture.get_path_span(tcx);
 
-        let by_move_coroutine_ty = tcx
-            .instantiate_bound_regions_with_erased(parent_closure_args.coroutine_closure_sig())
-            .to_coroutine_given_kind_and_upvars(
-                tcx,
-                parent_closure_args.parent_args(),
-                coroutine_def_id.to_def_id(),
-                ty::ClosureKind::FnOnce,
-                tcx.lifetimes.re_erased,
-                parent_closure_args.tupled_upvars_ty(),
-                parent_closure_args.coroutine_captures_by_ref_ty(),
+            (
+                FieldIdx::from_usize(child_field_idx + num_args),
+                (
+                    FieldIdx::from_usize(parent_field_idx + num_args),
+                    parent_capture_ty,
+                    deref_adjustment,
+                    child_precise_captures,
+                    parent_capture_span,
+                ),
+            )
+        },
+    ) {
+        field_remapping[child_field_idx] = Some(remapping);
+    }
+
+    if coroutine_kind == ty::ClosureKind::FnOnce {
+        // During error recovery, the parent may have captures that the child doesn't use.
+        let num_remapped = field_remapping.iter().flatten().count();
+        let num_parent_captures = tcx.closure_captures(parent_def_id).len();
+        if num_remapped != num_parent_captures {
+            tcx.dcx().span_delayed_bug(
+                body.span,
+                format!(
+                    "`{}` uses {num_remapped} of the {num_parent_captures} captures of its \
+                    `FnOnce` parent",
+                    tcx.def_path_str(coroutine_def_id),
+                ),
             );
+        }
+        return None;
+    }
 
-        let mut by_move_body = body.clone();
-        MakeByMoveBody { tcx, field_remapping, by_move_coroutine_ty }.visit_body(&mut by_move_body);
-        dump_mir(tcx, false, "coroutine_by_move", &0, &by_move_body, |_, _| Ok(()));
-        // FIXME: use query feeding to generate the body right here and then only store the `DefId` of the new body.
-        by_move_body.source = mir::MirSource::from_instance(InstanceKind::CoroutineKindShim {
-            coroutine_def_id: coroutine_def_id.to_def_id(),
+    // When the parent coroutine-closure is generic, the types of its captures may still
+    // mention projections that must be normalized before they are installed in the
+    // by-move body, or they will fail to normalize later on.
+    let param_env = tcx.param_env(parent_def_id);
+    let tupled_upvars_ty = parent_closure_args.tupled_upvars_ty();
+    let Ok(tupled_upvars_ty) = tcx.try_normalize_erasing_regions(param_env, tupled_upvars_ty)
+    else {
+        tcx.dcx().emit_err(errors::UnnormalizedByMoveUpvars {
+            span: tcx.def_span(parent_def_id),
+            upvars_ty: tupled_upvars_ty,
         });
-        body.coroutine.as_mut().unwrap().by_move_body = Some(by_move_body);
+        return None;
+    };
+
+    let by_move_coroutine_ty = tcx
+        .instantiate_bound_regions_with_erased(parent_closure_args.coroutine_closure_sig())
+        .to_coroutine_given_kind_and_upvars(
+            tcx,
+            parent_closure_args.parent_args(),
+            coroutine_def_id.to_def_id(),
+            ty::ClosureKind::FnOnce,
+            tcx.lifetimes.re_erased,
+            tupled_upvars_ty,
+            parent_closure_args.coroutine_captures_by_ref_ty(),
+        );
+    // The late-bound regions of the signature were erased above, so the only regions
+    // left are the parent's early-bound ones, `'static`, and erased regions. Anything
+    // else would leak into the self type of the by-move body and break codegen later.
+    debug_assert!(
+        !by_move_coroutine_ty.has_escaping_bound_vars()
+            && !by_move_coroutine_ty.has_infer_regions()
+            && !by_move_coroutine_ty.has_placeholders(),
+        "unexpected regions in by-move coroutine type: {by_move_coroutine_ty}"
+    );
+
+    // Apart from `source`, which names the shim, and the upvar accesses and self type, which
+    // `MakeByMoveBody` rewrites, every field of the built body is kept as is:
+    // - `phase` and `pass_count` must stay those of the built body: the by-move body is
+    //   attached at the start of `mir_promoted`, and then goes through the same passes and
+    //   phase changes as the by-ref body, which reset `pass_count` at each phase change.
+    // - `coroutine` keeps the yield, resume and drop types, which don't mention upvars. The
+    //   layout and the drop glue are computed later by the `StateTransform` pass, separately
+    //   for each body, and the nested `by_move_body` is not set yet on a built body.
+    // - `required_consts` is still empty. `mir_promoted` only collects it for the by-ref body,
+    //   so it is collected below, from the rewritten body. `mentioned_items` is collected
+    //   later, for each body, by the `MentionedItems` pass.
+    // - `tainted_by_errors`, `injection_phase` and the coverage info are properties of the
+    //   source code, which both bodies share.
+    //
+    // When the body uses every upvar exactly as the parent captures it, e.g. when the closure
+    // only captures zero-sized values that it moves out of, no place needs to be rewritten, and
+    // only the self type changes.
+    let needs_remapping = field_remapping.iter_enumerated().any(|(child_field_idx, remapping)| {
+        remapping.is_some_and(
+            |(parent_field_idx, parent_capture_ty, deref_adjustment, bridging, _)| {
+                parent_field_idx != child_field_idx
+                    || deref_adjustment != DerefAdjustment::None
+                    || !bridging.is_empty()
+                    || parent_capture_ty != args.upvar_tys()[child_field_idx.index()]
+            },
+        )
+    });
+    let mut by_move_body = (*body).clone();
+    let mut make_by_move_body = MakeByMoveBody { tcx, field_remapping, by_move_coroutine_ty };
+    if needs_remapping {
+        make_by_move_body.visit_body(&mut by_move_body);
+    } else {
+        by_move_body.local_decls[ty::CAPTURE_STRUCT_LOCAL].ty = by_move_coroutine_ty;
+    }
+    let mut required_consts = Vec::new();
+    let mut required_consts_visitor = RequiredConstsVisitor::new(&mut required_consts);
+    for (bb, bb_data) in traversal::reverse_postorder(&by_move_body) {
+        required_consts_visitor.visit_basic_block_data(bb, bb_data);
     }
+    by_move_body.required_consts = required_consts;
+    dump_mir(tcx, false, "coroutine_by_move", &0, &by_move_body, |pass_where, out| {
+        if let PassWhere::BeforeCFG = pass_where {
+            for (child_field_idx, remapping) in make_by_move_body.field_remapping.iter_enumerated()
+            {
+                if let Some((parent_field_idx, _, _, _, span)) = *remapping {
+                    writeln!(
+                        out,
+                        "// field {} <- parent field {} (captured at {})",
+                        child_field_idx.index(),
+                        parent_field_idx.index(),
+                        tcx.sess.source_map().span_to_embeddable_string(span),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    });
+    by_move_body.source = mir::MirSource::from_instance(InstanceKind::CoroutineKindShim {
+        coroutine_def_id: coroutine_def_id.to_def_id(),
+    });
+    Some(by_move_body)
+}
+
+/// How the projections of a child capture must be adjusted so that they apply to the
+/// corresponding upvar of the parent coroutine-closure.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum DerefAdjustment {
+    /// The parent and the child capture the same way.
+    None,
+    /// The parent captures by-move and the child by-ref: peel off the child's leading deref.
+    Peel,
+    /// The parent captures by-ref and the child by-move: deref the parent's upvar.
+    Add,
 }
 
+/// Rewrites the upvar accesses and the self type of a clone of the by-ref body.
+///
+/// The by-move body keeps the basic blocks of the by-ref body, so the unwind actions are left
+/// untouched. Should cleanup blocks ever need remapping, overriding
+/// `MutVisitor::visit_unwind_action` is enough, since every unwind edge is visited through it.
 struct MakeByMoveBody<'tcx> {
     tcx: TyCtxt<'tcx>,
-    field_remapping: UnordMap<FieldIdx, (FieldIdx, Ty<'tcx>, bool, &'tcx [Projection<'tcx>])>,
+    field_remapping: IndexVec<
+        FieldIdx,
+        Option<(FieldIdx, Ty<'tcx>, DerefAdjustment, &'tcx [Projection<'tcx>], Span)>,
+    >,
     by_move_coroutine_ty: Ty<'tcx>,
 }
 
@@ -218,19 +372,20 @@ fn visit_place(
         location: mir::Location,
     ) {
         // Initializing an upvar local always starts with `CAPTURE_STRUCT_LOCAL` and a
-        // field projection. If this is in `field_remapping`, then it must not be an
+        // field projection. If this has a `field_remapping`, then it must not be an
         // arg from calling the closure, but instead an upvar.
         if place.local == ty::CAPTURE_STRUCT_LOCAL
             && let Some((&mir::ProjectionElem::Field(idx, _), projection)) =
                 place.projection.split_first()
-            && let Some(&(remapped_idx, remapped_ty, needs_deref, bridging_projections)) =
-                self.field_remapping.get(&idx)
+            && let Some(&Some(remapping)) = self.field_remapping.get(idx)
         {
+            let (remapped_idx, remapped_ty, deref_adjustment, bridging_projections, _) = remapping;
+
             // As noted before, if the parent closure captures a field by value, and
             // the child captures a field by ref, then for the by-move body we're
             // generating, we also are taking that field by value. Peel off a deref,
             // since a layer of ref'ing has now become redundant.
-            let final_projections = if needs_deref {
+            let final_projections = if let DerefAdjustment::Peel = deref_adjustment {
                 let Some((mir::ProjectionElem::Deref, projection)) = projection.split_first()
                 else {
                     bug!(
@@ -257,6 +412,12 @@ fn visit_place(
                 _ => unreachable!("precise captures only through fields and derefs"),
             });
 
+            // If the parent captured by ref while the child captured by value, the upvar
+            // we get from the parent is a reference to the place the child captured, so
+            // we deref it before applying any of the bridging projections.
+            let added_deref =
+                (deref_adjustment == DerefAdjustment::Add).then_some(mir::ProjectionElem::Deref);
+
             // We start out with an adjusted field index (and ty), representing the
             // upvar that we get from our parent closure. We apply any of the additional
             // projections to make sure that to the rest of the body of the closure, the
@@ -266,6 +427,7 @@ fn visit_place(
                 projection: self.tcx.mk_place_elems_from_iter(
                     [mir::ProjectionElem::Field(remapped_idx, remapped_ty)]
                         .into_iter()
+                        .chain(added_deref)
                         .chain(bridging_projections)
                         .chain(final_projections.iter().copied()),
                 ),
@@ -0,0 +1,83 @@
+//! This pass makes structurally equal constants of a body share a single value. It is off by
+//! default, but can be enabled on the command line (`-Zmir-enable-passes=+DeduplicateConsts`).
+//!
+//! Most constants are already interned, so equal constants are equal values. The exception is
+//! constants stored in memory, like array literals: each occurrence gets its own allocation,
+//! even when the contents are identical. Those are rewritten to use the first allocation with
+//! the same contents.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::mir::interpret::ConstAllocation;
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{Ty, TyCtxt};
+use rustc_session::Session;
+use rustc_target::abi::Size;
+
+pub struct DeduplicateConsts;
+
+impl<'tcx> MirPass<'tcx> for DeduplicateConsts {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running DeduplicateConsts on {:?}", body.source);
+        let mut deduplicator = ConstDeduplicator::new(tcx);
+        deduplicator.visit_body_preserves_cfg(body);
+        debug!("{} deduplicated constants in {:?}", deduplicator.deduplicated, body.source);
+    }
+}
+
+/// The contents of a constant, independently of where it is stored.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum ConstContents<'tcx> {
+    Indirect { alloc: ConstAllocation<'tcx>, offset: Size },
+    Direct(ConstValue<'tcx>),
+}
+
+/// Rewrites every constant operand whose value is equal to the value of a previously visited
+/// constant of the same type so that both use the same value.
+///
+/// Constants are keyed on their type as well as their contents, since the same bytes can
+/// mean different values at different types.
+struct ConstDeduplicator<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    values: FxHashMap<(Ty<'tcx>, ConstContents<'tcx>), ConstValue<'tcx>>,
+    /// The number of constant operands that were rewritten.
+    deduplicated: usize,
+}
+
+impl<'tcx> ConstDeduplicator<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        ConstDeduplicator { tcx, values: FxHashMap::default(), deduplicated: 0 }
+    }
+
+    fn contents(&self, value: ConstValue<'tcx>) -> ConstContents<'tcx> {
+        match value {
+            ConstValue::Indirect { alloc_id, offset } => ConstContents::Indirect {
+                alloc: self.tcx.global_alloc(alloc_id).unwrap_memory(),
+                offset,
+            },
+            _ => ConstContents::Direct(value),
+        }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for ConstDeduplicator<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_const_operand(&mut self, constant: &mut ConstOperand<'tcx>, location: Location) {
+        if let Const::Val(value, ty) = constant.const_ {
+            let contents = self.contents(value);
+            let canonical = *self.values.entry((ty, contents)).or_insert(value);
+            if canonical != value {
+                constant.const_ = Const::Val(canonical, ty);
+                self.deduplicated += 1;
+            }
+        }
+        self.super_const_operand(constant, location);
+    }
+}
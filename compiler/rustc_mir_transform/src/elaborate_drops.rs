@@ -259,7 +259,9 @@ fn param_env(&self) -> ty::ParamEnv<'tcx> {
     fn create_drop_flag(&mut self, index: MovePathIndex, span: Span) {
         let patch = &mut self.patch;
         debug!("create_drop_flag({:?})", self.body.span);
-        self.drop_flags[index].get_or_insert_with(|| patch.new_temp(self.tcx.types.bool, span));
+        self.drop_flags[index].get_or_insert_with(|| {
+            patch.new_local_with_info(self.tcx.types.bool, span, LocalInfo::DropFlag)
+        });
     }
 
     fn drop_flag(&mut self, index: MovePathIndex) -> Option<Place<'tcx>> {
@@ -0,0 +1,74 @@
+//! This pass moves every `StorageLive` statement to the start of its block. It is off by
+//! default, but can be enabled on the command line (`-Zmir-enable-passes=+StorageLiveHoister`)
+//! to experiment with stack slot reuse.
+//!
+//! Starting the storage of a local earlier within the same block is valid as long as the
+//! local is not mentioned before the `StorageLive` in that block: moving it before a
+//! `StorageDead` of the local would leave the local dead, and moving it before any other use
+//! would make the storage start again after that use, losing the value the local had. Such
+//! markers are left in place.
+
+use rustc_index::bit_set::BitSet;
+use rustc_middle::mir::visit::{MutVisitor, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+pub struct StorageLiveHoister;
+
+impl<'tcx> MirPass<'tcx> for StorageLiveHoister {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running StorageLiveHoister on {:?}", body.source);
+        let mut hoister =
+            HoistStorageLive { tcx, mentioned: BitSet::new_empty(body.local_decls.len()) };
+        hoister.visit_body_preserves_cfg(body);
+    }
+}
+
+struct HoistStorageLive<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The locals that are mentioned by a statement kept in place earlier in the current block.
+    mentioned: BitSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for HoistStorageLive<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_basic_block_data(&mut self, block: BasicBlock, data: &mut BasicBlockData<'tcx>) {
+        self.mentioned.clear();
+
+        let mut hoisted = Vec::new();
+        let mut rest = Vec::with_capacity(data.statements.len());
+        for (statement_index, statement) in data.statements.drain(..).enumerate() {
+            match statement.kind {
+                StatementKind::StorageLive(local) if !self.mentioned.contains(local) => {
+                    hoisted.push(statement)
+                }
+                _ => {
+                    let location = Location { block, statement_index };
+                    MentionedLocals(&mut self.mentioned).visit_statement(&statement, location);
+                    rest.push(statement);
+                }
+            }
+        }
+
+        // The hoisted markers keep their relative order.
+        hoisted.extend(rest);
+        data.statements = hoisted;
+    }
+}
+
+/// Records the locals mentioned by the visited statements.
+struct MentionedLocals<'a>(&'a mut BitSet<Local>);
+
+impl<'tcx> Visitor<'tcx> for MentionedLocals<'_> {
+    fn visit_local(&mut self, local: Local, _context: PlaceContext, _location: Location) {
+        self.0.insert(local);
+    }
+}
@@ -0,0 +1,43 @@
+//! Moves all the source information of a body into a single inlined scope. This is not a MIR
+//! pass, since it needs to be told which scope to use, but it can be used directly by external
+//! drivers that inline bodies themselves.
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+
+/// Rewrites every `SourceInfo` of a callee body to be in `inlined_scope`.
+///
+/// The spans themselves are kept, so that debuggers can still step through the source of the
+/// callee: that the code comes from an inlined call is recorded by the scope instead, whose
+/// `inlined` field should be `Some((callee, call_site_span))`. Only the dummy spans, which
+/// don't point anywhere in the callee, are replaced with `call_site_span`.
+///
+/// Unlike the `Inline` pass, which keeps the scopes of the callee and only attaches the
+/// outermost one to the call site, this collapses all of them into `inlined_scope`, so the
+/// debuginfo of the callee's variables is no longer nested in their own scopes.
+pub struct InlineSpanRewriter<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub call_site_span: Span,
+    pub inlined_scope: SourceScope,
+}
+
+impl<'tcx> InlineSpanRewriter<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, call_site_span: Span, inlined_scope: SourceScope) -> Self {
+        InlineSpanRewriter { tcx, call_site_span, inlined_scope }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for InlineSpanRewriter<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_source_info(&mut self, source_info: &mut SourceInfo) {
+        source_info.scope = self.inlined_scope;
+        if source_info.span.is_dummy() {
+            source_info.span = self.call_site_span;
+        }
+    }
+}
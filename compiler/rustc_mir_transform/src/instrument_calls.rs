@@ -0,0 +1,91 @@
+//! Inserts a call to a logging function before every call of a body. This is not a MIR pass,
+//! since it needs to be told which function to call, but it can be used directly by external
+//! drivers, e.g. for tracing builds.
+
+use rustc_hir::def_id::DefId;
+use rustc_index::bit_set::BitSet;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+
+/// Makes every block that ends in a `Call` call `logger` first.
+///
+/// The original call is moved to a new block, and the block that used to end in it now ends in
+/// a call to `logger` that continues to the new block. The statements of the block are
+/// therefore executed before `logger` is called, and nothing is executed between the call to
+/// `logger` and the original call.
+///
+/// The call to `logger` terminates the program if `logger` unwinds. It can't use the unwind
+/// action of the original call: that cleanup assumes the arguments of the original call were
+/// moved into the callee, so the arguments that are moved would be leaked.
+///
+/// Calls to `logger` itself are not instrumented, and neither are the calls that are already
+/// alone in a block whose only predecessor ends in a call to `logger`, so instrumenting a body
+/// twice has the same effect as doing it once.
+///
+/// `logger` must be a function without generic parameters or arguments, returning `()`, and
+/// should not unwind.
+pub struct CallInstrumenter {
+    pub logger: DefId,
+}
+
+impl CallInstrumenter {
+    pub fn instrument<'tcx>(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let mut new_blocks = Vec::new();
+        let cur_len = body.basic_blocks.len();
+        let mut logger_destination = None;
+
+        // The blocks of the calls that a previous instrumentation of the body moved out.
+        let mut instrumented = BitSet::new_empty(cur_len);
+        for block in body.basic_blocks.iter() {
+            if let TerminatorKind::Call { func, target: Some(target), .. } =
+                &block.terminator().kind
+                && self.is_logger(func)
+                && body.basic_blocks[*target].statements.is_empty()
+                && body.basic_blocks.predecessors()[*target].len() == 1
+            {
+                instrumented.insert(*target);
+            }
+        }
+
+        for (bb, block) in body.basic_blocks.as_mut().iter_enumerated_mut() {
+            let Some(terminator) = &mut block.terminator else { continue };
+            let TerminatorKind::Call { ref func, .. } = terminator.kind else { continue };
+            if self.is_logger(func) || instrumented.contains(bb) {
+                continue;
+            }
+
+            let source_info = terminator.source_info;
+            let destination = *logger_destination.get_or_insert_with(|| {
+                body.local_decls.push(LocalDecl::new(tcx.types.unit, body.span))
+            });
+            let logger_call = TerminatorKind::Call {
+                func: Operand::function_handle(tcx, self.logger, [], source_info.span),
+                args: Box::new([]),
+                destination: destination.into(),
+                // Get the index the block of the original call will have when inserted.
+                target: Some(BasicBlock::from_usize(cur_len + new_blocks.len())),
+                unwind: UnwindAction::Terminate(if block.is_cleanup {
+                    UnwindTerminateReason::InCleanup
+                } else {
+                    UnwindTerminateReason::Abi
+                }),
+                call_source: CallSource::Misc,
+                fn_span: source_info.span,
+            };
+            let call = std::mem::replace(&mut terminator.kind, logger_call);
+            new_blocks.push(BasicBlockData {
+                statements: vec![],
+                terminator: Some(Terminator { source_info, kind: call }),
+                is_cleanup: block.is_cleanup,
+            });
+        }
+
+        debug!("Instrumented {} calls", new_blocks.len());
+
+        body.basic_blocks_mut().extend(new_blocks);
+    }
+
+    fn is_logger(&self, func: &Operand<'_>) -> bool {
+        func.const_fn_def().is_some_and(|(def_id, _)| def_id == self.logger)
+    }
+}
@@ -0,0 +1,57 @@
+//! This pass replaces `Len` of a fixed-size array with the length of the array type. It is off by
+//! default, but can be enabled on the command line (`-Zmir-enable-passes=+ConstArrayLen`).
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::Session;
+
+pub struct ConstArrayLen;
+
+impl<'tcx> MirPass<'tcx> for ConstArrayLen {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running ConstArrayLen on {:?}", body.source);
+        let mut visitor = LenToConst::new(tcx, &body.local_decls);
+        for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Replaces every `Rvalue::Len(place)` where `place` is an array `[T; N]` with `N`.
+///
+/// The length of a slice is only known at runtime, so `Len` of a place of type `[T]` is left
+/// unchanged. The length of an array whose length is a const parameter is replaced with that
+/// parameter.
+struct LenToConst<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+}
+
+impl<'a, 'tcx> LenToConst<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, local_decls: &'a LocalDecls<'tcx>) -> Self {
+        LenToConst { tcx, local_decls }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for LenToConst<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Len(place) = *rvalue
+            && let ty::Array(_, len) = *place.ty(self.local_decls, self.tcx).ty.kind()
+        {
+            let const_ = Const::from_ty_const(len, self.tcx.types.usize, self.tcx);
+            let span = self.local_decls[place.local].source_info.span;
+            let constant = ConstOperand { span, const_, user_ty: None };
+            *rvalue = Rvalue::Use(Operand::Constant(Box::new(constant)));
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
@@ -52,11 +52,15 @@
 mod add_moves_for_packed_drops;
 mod add_retag;
 mod add_subtyping_projections;
+pub mod assign_ids;
 mod check_alignment;
 mod check_const_item_mutation;
 mod check_packed_ref;
 // This pass is public to allow external drivers to perform MIR cleanup
 pub mod cleanup_post_borrowck;
+mod coalesce_copies;
+mod collapse_goto_chains;
+mod const_span_fixup;
 mod copy_prop;
 mod coroutine;
 mod cost_checker;
@@ -66,6 +70,7 @@
 mod dataflow_const_prop;
 mod dead_store_elimination;
 mod deduce_param_attrs;
+mod deduplicate_consts;
 mod deduplicate_blocks;
 mod deref_separator;
 mod dest_prop;
@@ -77,21 +82,32 @@
 mod ffi_unwind_calls;
 mod function_item_references;
 mod gvn;
+mod hoist_storage_live;
 pub mod inline;
+pub mod inline_spans;
+pub mod instrument_calls;
 mod instsimplify;
 mod jump_threading;
 mod known_panics_lint;
 mod large_enums;
+mod len_to_const;
 mod lint;
+mod lower_checked_arith;
 mod lower_intrinsics;
 mod lower_slice_len;
+mod lower_trivial_discriminants;
+mod lower_zero_repeats;
 mod match_branches;
 mod mentioned_items;
+mod move_to_copy;
 mod multiple_return_terminators;
+mod normalize_bool_switches;
+mod normalize_commutative_ops;
 mod nrvo;
 mod prettify;
 mod promote_consts;
 mod ref_prop;
+mod remove_bounds_checks;
 mod remove_noop_landing_pads;
 mod remove_place_mention;
 mod remove_storage_markers;
@@ -110,6 +126,7 @@
 mod sroa;
 mod unreachable_enum_branching;
 mod unreachable_prop;
+mod unreachable_to_abort;
 mod validate;
 
 use rustc_const_eval::check_consts::{self, ConstCx};
@@ -130,6 +147,7 @@ pub fn provide(providers: &mut Providers) {
         mir_drops_elaborated_and_const_checked,
         mir_for_ctfe,
         mir_coroutine_witnesses: coroutine::mir_coroutine_witnesses,
+        coroutine_by_move_body: coroutine::coroutine_by_move_body,
         optimized_mir,
         is_mir_available,
         is_ctfe_mir_available: |tcx, did| is_mir_available(tcx, did),
@@ -295,10 +313,6 @@ fn mir_built(tcx: TyCtxt<'_>, def: LocalDefId) -> &Steal<Body<'_>> {
             &Lint(check_packed_ref::CheckPackedRef),
             &Lint(check_const_item_mutation::CheckConstItemMutation),
             &Lint(function_item_references::FunctionItemReferences),
-            // If this is an async closure's output coroutine, generate
-            // by-move and by-mut bodies if needed. We do this first so
-            // they can be optimized in lockstep with their parent bodies.
-            &coroutine::ByMoveBody,
             // What we need to do constant evaluation.
             &simplify::SimplifyCfg::Initial,
             &rustc_peek::SanityCheck, // Just a lint
@@ -333,6 +347,16 @@ fn mir_promoted(
     };
     // has_ffi_unwind_calls query uses the raw mir, so make sure it is run.
     tcx.ensure_with_value().has_ffi_unwind_calls(def);
+    // coroutine_by_move_body query uses the raw mir, so make sure it is run. It reads the
+    // result of `mir_built`, so it can't run as one of its passes like it used to, and builds
+    // the by-move body after `SimplifyCfg::Initial` instead of before it. That pass only
+    // removes dead blocks and merges `Goto` chains, so the by-move body is the same as if it
+    // had been built from the simplified by-ref body, which it is optimized in lockstep with.
+    if let Some(hir::CoroutineKind::Desugared(_, hir::CoroutineSource::Closure)) =
+        tcx.coroutine_kind(def)
+    {
+        tcx.ensure_with_value().coroutine_by_move_body(def);
+    }
     let mut body = tcx.mir_built(def).steal();
     if let Some(error_reported) = const_qualifs.tainted_by_errors {
         body.tainted_by_errors = Some(error_reported);
@@ -352,7 +376,14 @@ fn mir_promoted(
     pm::run_passes(
         tcx,
         &mut body,
-        &[&promote_pass, &simplify::SimplifyCfg::PromoteConsts, &coverage::InstrumentCoverage],
+        &[
+            // If this is an async closure's output coroutine, attach its by-move body.
+            // We do this first so it can be optimized in lockstep with its parent body.
+            &coroutine::ByMoveBody,
+            &promote_pass,
+            &simplify::SimplifyCfg::PromoteConsts,
+            &coverage::InstrumentCoverage,
+        ],
         Some(MirPhase::Analysis(AnalysisPhase::Initial)),
     );
 
@@ -572,10 +603,22 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             // Has to be done before inlining, otherwise actual call will be almost always inlined.
             // Also simple, so can just do first
             &lower_slice_len::LowerSliceLenCalls,
+            // Constant lengths of arrays, off by default.
+            &len_to_const::ConstArrayLen,
+            // Splitting of checked arithmetic, off by default.
+            &lower_checked_arith::LowerCheckedArith,
             // Perform inlining, which may add a lot of code.
             &inline::Inline,
+            // Empty arrays for repeats of zero elements, off by default.
+            &lower_zero_repeats::LowerZeroRepeats,
+            // Constant discriminants of single-variant types, off by default.
+            &lower_trivial_discriminants::LowerTrivialDiscriminants,
+            // Unchecked indexing experiment, off by default.
+            &remove_bounds_checks::RemoveBoundsChecks,
             // Code from other crates may have storage markers, so this needs to happen after inlining.
             &remove_storage_markers::RemoveStorageMarkers,
+            // Stack slot reuse experiment, off by default.
+            &hoist_storage_live::StorageLiveHoister,
             // Inlining and instantiation may introduce ZST and useless drops.
             &remove_zsts::RemoveZsts,
             &remove_unneeded_drops::RemoveUnneededDrops,
@@ -592,6 +635,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             // inst combine is after MatchBranchSimplification to clean up Ne(_1, false)
             &multiple_return_terminators::MultipleReturnTerminators,
             &instsimplify::InstSimplify,
+            // Coalescing of copies of single-assignment locals, off by default.
+            &coalesce_copies::CopyCoalescer,
             &simplify::SimplifyLocals::BeforeConstProp,
             &dead_store_elimination::DeadStoreElimination::Initial,
             &gvn::GVN,
@@ -605,6 +650,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &dest_prop::DestinationPropagation,
             &o1(simplify_branches::SimplifyConstCondition::Final),
             &o1(remove_noop_landing_pads::RemoveNoopLandingPads),
+            // Bypassing of empty `Goto` blocks, off by default.
+            &collapse_goto_chains::GotoChainCollapser,
             &o1(simplify::SimplifyCfg::Final),
             &copy_prop::CopyProp,
             &dead_store_elimination::DeadStoreElimination::Final,
@@ -612,10 +659,20 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &simplify::SimplifyLocals::Final,
             &multiple_return_terminators::MultipleReturnTerminators,
             &deduplicate_blocks::DeduplicateBlocks,
+            // Sharing of equal constants, off by default.
+            &deduplicate_consts::DeduplicateConsts,
             &large_enums::EnumSizeOpt { discrepancy: 128 },
+            // Hardening of `Unreachable` terminators, off by default.
+            &unreachable_to_abort::UnreachableToAbort,
             // Some cleanup necessary at least for LLVM and potentially other codegen backends.
             &add_call_guards::CriticalCallEdges,
+            // Canonicalization of moves of `Copy` values for MIR checkers, off by default.
+            &move_to_copy::ReplaceCopyMoves,
+            // Canonical operand order for commutative operations, off by default.
+            &normalize_commutative_ops::NormalizeCommutativeOps,
             // Cleanup for human readability, off by default.
+            &normalize_bool_switches::NormalizeBoolSwitches,
+            &const_span_fixup::FixupConstSpans,
             &prettify::ReorderBasicBlocks,
             &prettify::ReorderLocals,
             // Dump the end result for testing and debugging purposes.
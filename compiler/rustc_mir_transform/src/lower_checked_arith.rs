@@ -0,0 +1,150 @@
+//! This pass splits checked additions and subtractions into the wrapping operation and an explicit
+//! computation of the overflow flag. It is off by default, but can be enabled on the command line
+//! (`-Zmir-enable-passes=+LowerCheckedArith`).
+//!
+//! With overflow checks, `a + b` is built as `_t = AddWithOverflow(a, b)` followed by an
+//! `Assert` of `!_t.1` with an `Overflow` message, which already has the unwind edge of the
+//! operation. This pass makes that `Assert` check the computed overflow flag directly, and
+//! keeps its target and unwind edge, so no block needs to be added.
+
+use rustc_middle::mir::patch::MirPatch;
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_session::Session;
+use rustc_span::Span;
+
+pub struct LowerCheckedArith;
+
+impl<'tcx> MirPass<'tcx> for LowerCheckedArith {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running LowerCheckedArith on {:?}", body.source);
+        lower_checked_arith(tcx, body);
+    }
+}
+
+/// Splits every checked addition and subtraction of `body`, see `CheckedArithLowering`.
+fn lower_checked_arith<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+    let patch = MirPatch::new(body);
+    let mut lowering =
+        CheckedArithLowering { tcx, patch, local_decls: &body.local_decls, last_lowered: None };
+    for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+        lowering.visit_basic_block_data(block, data);
+    }
+    lowering.patch.apply(body);
+}
+
+/// Rewrites `_t = AddWithOverflow(a, b)` into
+///
+/// ```text
+/// _r = Add(a, b);
+/// _o = <overflow of a + b, computed from a, b and _r>;
+/// _t = (move _r, move _o);
+/// ```
+///
+/// and `SubWithOverflow` in the same way. `Add` and `Sub` wrap on overflow, so `_r` is the
+/// same value as `_t.0` was. For unsigned integers, the addition overflowed if `_r < a`, and
+/// the subtraction if `a < b`. For signed integers, the sign bit of `(a ^ _r) & (b ^ _r)`,
+/// respectively `(a ^ b) & (a ^ _r)`, is set if the operation overflowed.
+///
+/// `MulWithOverflow` is left unchanged, since its overflow can't be computed from the
+/// wrapped result without a wider integer type.
+///
+/// When the assignment is the last statement of its block and the block ends in an `Assert`
+/// of `_t.1`, as it does with overflow checks, the `Assert` checks `_o` instead, so that the
+/// overflow computation directly feeds the `Assert`, which keeps its target and unwind edge.
+struct CheckedArithLowering<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    patch: MirPatch<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+    /// The last lowered assignment, with its destination and overflow flag.
+    last_lowered: Option<(Location, Place<'tcx>, Local)>,
+}
+
+impl<'tcx> CheckedArithLowering<'_, 'tcx> {
+    /// Assigns `op(lhs, rhs)` to a new temporary before `location`, and returns the temporary.
+    ///
+    /// `operand_ty` is the type of both operands. It can't be computed from the operands,
+    /// since they may be temporaries of the patch, which are not in `local_decls` yet.
+    fn add_binary_op(
+        &mut self,
+        location: Location,
+        span: Span,
+        op: BinOp,
+        operand_ty: Ty<'tcx>,
+        lhs: Operand<'tcx>,
+        rhs: Operand<'tcx>,
+    ) -> Local {
+        let temp = self.patch.new_temp(op.ty(self.tcx, operand_ty, operand_ty), span);
+        self.patch.add_assign(location, temp.into(), Rvalue::BinaryOp(op, Box::new((lhs, rhs))));
+        temp
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for CheckedArithLowering<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_assign(
+        &mut self,
+        place: &mut Place<'tcx>,
+        rvalue: &mut Rvalue<'tcx>,
+        location: Location,
+    ) {
+        let Rvalue::BinaryOp(op, box (ref lhs, ref rhs)) = *rvalue else { return };
+        let wrapping_op = match op {
+            BinOp::AddWithOverflow => BinOp::Add,
+            BinOp::SubWithOverflow => BinOp::Sub,
+            _ => return,
+        };
+        // The operands are integers, so they can be copied to be used more than once.
+        let (lhs, rhs) = (lhs.to_copy(), rhs.to_copy());
+        let ty = lhs.ty(self.local_decls, self.tcx);
+        let span = self.local_decls[place.local].source_info.span;
+
+        let result = self.add_binary_op(location, span, wrapping_op, ty, lhs.clone(), rhs.clone());
+        let result_copy = Operand::Copy(result.into());
+        let overflow = if ty.is_signed() {
+            let (x, y) = if wrapping_op == BinOp::Add {
+                ((lhs, result_copy.clone()), (rhs, result_copy))
+            } else {
+                ((lhs.clone(), rhs), (lhs, result_copy))
+            };
+            let x = self.add_binary_op(location, span, BinOp::BitXor, ty, x.0, x.1);
+            let y = self.add_binary_op(location, span, BinOp::BitXor, ty, y.0, y.1);
+            let (x, y) = (Operand::Move(x.into()), Operand::Move(y.into()));
+            let sign = self.add_binary_op(location, span, BinOp::BitAnd, ty, x, y);
+            let const_ = Const::from_bits(self.tcx, 0, ty::ParamEnv::empty().and(ty));
+            let zero = Operand::Constant(Box::new(ConstOperand { span, user_ty: None, const_ }));
+            self.add_binary_op(location, span, BinOp::Lt, ty, Operand::Move(sign.into()), zero)
+        } else if wrapping_op == BinOp::Add {
+            self.add_binary_op(location, span, BinOp::Lt, ty, result_copy, lhs)
+        } else {
+            self.add_binary_op(location, span, BinOp::Lt, ty, lhs, rhs)
+        };
+
+        let fields = [Operand::Move(result.into()), Operand::Copy(overflow.into())];
+        *rvalue = Rvalue::Aggregate(Box::new(AggregateKind::Tuple), fields.into_iter().collect());
+        self.last_lowered = Some((location, *place, overflow));
+    }
+
+    fn visit_terminator(&mut self, terminator: &mut Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Assert { cond: Operand::Copy(cond) | Operand::Move(cond), .. } =
+            &mut terminator.kind
+            && let Some((lowered, place, overflow)) = self.last_lowered
+            && lowered.successor_within_block() == location
+            && cond.local == place.local
+            && let [ref place_projection @ .., ProjectionElem::Field(field, _)] =
+                cond.projection[..]
+            && place_projection == &place.projection[..]
+            && field.index() == 1
+        {
+            *cond = overflow.into();
+        }
+    }
+}
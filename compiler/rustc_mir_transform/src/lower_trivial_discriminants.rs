@@ -0,0 +1,77 @@
+//! This pass replaces the `Discriminant` reads of types with a single possible discriminant with a
+//! constant. It is off by default, but can be enabled on the command line
+//! (`-Zmir-enable-passes=+LowerTrivialDiscriminants`).
+//!
+//! Matching on such a type doesn't read its discriminant, so these reads mostly come from
+//! `mem::discriminant` and the `discriminant_value` intrinsic, often in generic code that was
+//! inlined.
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::Session;
+use rustc_target::abi::VariantIdx;
+
+pub struct LowerTrivialDiscriminants;
+
+impl<'tcx> MirPass<'tcx> for LowerTrivialDiscriminants {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running LowerTrivialDiscriminants on {:?}", body.source);
+        let mut visitor = TrivialDiscriminant::new(tcx, &body.local_decls);
+        for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Replaces every `Rvalue::Discriminant(place)` whose result doesn't depend on the value of
+/// `place` with a constant of the discriminant type.
+///
+/// This is the case of enums with a single variant, whose discriminant is the one of that
+/// variant, including when it is given explicitly, e.g. `5` for `enum E { A = 5 }`, and of
+/// structs and unions, whose discriminant is always `0u8`. The reads of enums without variants
+/// are left alone, since they can't be executed, and so are the ones of types that are not
+/// known to be an ADT, like type parameters, since their discriminant type isn't known either.
+struct TrivialDiscriminant<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+}
+
+impl<'a, 'tcx> TrivialDiscriminant<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, local_decls: &'a LocalDecls<'tcx>) -> Self {
+        TrivialDiscriminant { tcx, local_decls }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for TrivialDiscriminant<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Discriminant(place) = rvalue
+            && let place_ty = place.ty(self.local_decls, self.tcx).ty
+            && let ty::Adt(adt_def, _) = *place_ty.kind()
+        {
+            let discr = if !adt_def.is_enum() {
+                Some(0)
+            } else if adt_def.variants().len() == 1 {
+                Some(adt_def.discriminant_for_variant(self.tcx, VariantIdx::ZERO).val)
+            } else {
+                None
+            };
+            if let Some(discr) = discr {
+                let discr_ty = place_ty.discriminant_ty(self.tcx);
+                let const_ = Const::from_bits(self.tcx, discr, ty::ParamEnv::empty().and(discr_ty));
+                let span = self.local_decls[place.local].source_info.span;
+                let constant = ConstOperand { span, user_ty: None, const_ };
+                *rvalue = Rvalue::Use(Operand::Constant(Box::new(constant)));
+            }
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
@@ -0,0 +1,79 @@
+//! This pass replaces the `Repeat` rvalues whose count is zero with an empty array constant. It is
+//! off by default, but can be enabled on the command line
+//! (`-Zmir-enable-passes=+LowerZeroRepeats`).
+//!
+//! MIR building already turns a literal `[x; 0]` into a drop of `x` and an empty array, so a
+//! `Repeat` only has a count of zero once a generic count has been instantiated, e.g. `[x; N]`
+//! in a function inlined with `N = 0`.
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_session::Session;
+
+pub struct LowerZeroRepeats;
+
+impl<'tcx> MirPass<'tcx> for LowerZeroRepeats {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running LowerZeroRepeats on {:?}", body.source);
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let mut visitor = ZeroRepeatLowering::new(tcx, param_env, &body.local_decls);
+        for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Replaces every `Rvalue::Repeat(x, 0)` with an empty array constant of the same type, so
+/// that `x` isn't read at all.
+///
+/// Unless the count is literally `0` or `1`, the repeated value must be a constant or have a
+/// `Copy` type, which has no drop glue, so not reading it doesn't change what is dropped. The
+/// repeats that move a value whose type needs dropping are left alone anyway: drop elaboration
+/// considers the value moved out, so removing the move would make it be forgotten instead of
+/// dropped.
+struct ZeroRepeatLowering<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+}
+
+impl<'a, 'tcx> ZeroRepeatLowering<'a, 'tcx> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        param_env: ty::ParamEnv<'tcx>,
+        local_decls: &'a LocalDecls<'tcx>,
+    ) -> Self {
+        ZeroRepeatLowering { tcx, param_env, local_decls }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for ZeroRepeatLowering<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Repeat(operand, count) = rvalue
+            && count.try_eval_target_usize(self.tcx, self.param_env) == Some(0)
+        {
+            let elem_ty = operand.ty(self.local_decls, self.tcx);
+            if !(operand.is_move() && elem_ty.needs_drop(self.tcx, self.param_env)) {
+                let span = match operand {
+                    Operand::Constant(constant) => constant.span,
+                    Operand::Copy(place) | Operand::Move(place) => {
+                        self.local_decls[place.local].source_info.span
+                    }
+                };
+                let const_ = Const::zero_sized(Ty::new_array(self.tcx, elem_ty, 0));
+                let constant = ConstOperand { span, user_ty: None, const_ };
+                *rvalue = Rvalue::Use(Operand::Constant(Box::new(constant)));
+            }
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
@@ -0,0 +1,62 @@
+//! This pass turns `Operand::Move` into `Operand::Copy` when the moved place has a `Copy` type,
+//! since such a move is semantically a copy. It is off by default, but can be enabled on the
+//! command line (`-Zmir-enable-passes=+ReplaceCopyMoves`).
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, TyCtxt, TypeVisitableExt};
+use rustc_session::Session;
+
+pub struct ReplaceCopyMoves;
+
+impl<'tcx> MirPass<'tcx> for ReplaceCopyMoves {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running ReplaceCopyMoves on {:?}", body.source);
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let mut visitor = MoveToCopy::new(tcx, param_env, &body.local_decls);
+        for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Replaces every `Operand::Move` of a place whose type is `Copy` with an `Operand::Copy`.
+///
+/// A place whose type mentions generic parameters is always left as a move, even if the
+/// type is `Copy` under the given `param_env`: whether it is `Copy` may change once the
+/// body is instantiated, and the instantiated body should not differ from the generic one.
+struct MoveToCopy<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+}
+
+impl<'a, 'tcx> MoveToCopy<'a, 'tcx> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        param_env: ty::ParamEnv<'tcx>,
+        local_decls: &'a LocalDecls<'tcx>,
+    ) -> Self {
+        MoveToCopy { tcx, param_env, local_decls }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for MoveToCopy<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        if let Operand::Move(place) = *operand {
+            let ty = place.ty(self.local_decls, self.tcx).ty;
+            if !ty.has_param() && ty.is_copy_modulo_regions(self.tcx, self.param_env) {
+                *operand = Operand::Copy(place);
+            }
+        }
+        self.super_operand(operand, location);
+    }
+}
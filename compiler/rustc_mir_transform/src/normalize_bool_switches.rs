@@ -0,0 +1,58 @@
+//! This pass rewrites the `SwitchInt`s on booleans so that the `false` target is always the
+//! `otherwise` target, which makes dumped MIR easier to read. It is off by default, but can be
+//! enabled on the command line (`-Zmir-enable-passes=+NormalizeBoolSwitches`).
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+pub struct NormalizeBoolSwitches;
+
+impl<'tcx> MirPass<'tcx> for NormalizeBoolSwitches {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running NormalizeBoolSwitches on {:?}", body.source);
+        let mut visitor = BoolSwitchNormalizer::new(tcx, &body.local_decls);
+        // The values of the switches change, so the cached switch sources are invalidated.
+        for (block, data) in body.basic_blocks.as_mut().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Rewrites every `switchInt(b) -> [0: f, otherwise: t]` on a boolean `b` to the equivalent
+/// `switchInt(b) -> [1: t, otherwise: f]`.
+///
+/// This is the opposite of the form built by `TerminatorKind::if_`, and of the form that MIR
+/// building produces. Switches that are not on a boolean, or that already test for `true`,
+/// are left untouched.
+struct BoolSwitchNormalizer<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+}
+
+impl<'a, 'tcx> BoolSwitchNormalizer<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, local_decls: &'a LocalDecls<'tcx>) -> Self {
+        BoolSwitchNormalizer { tcx, local_decls }
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for BoolSwitchNormalizer<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_terminator(&mut self, terminator: &mut Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::SwitchInt { discr, targets } = &mut terminator.kind
+            && discr.ty(self.local_decls, self.tcx).is_bool()
+            && let Some((0, if_false, if_true)) = targets.as_static_if()
+        {
+            *targets = SwitchTargets::static_if(1, if_true, if_false);
+        }
+        self.super_terminator(terminator, location);
+    }
+}
@@ -0,0 +1,65 @@
+//! This pass puts the operands of commutative binary operations in a canonical order, so
+//! that `a + b` and `b + a` produce identical rvalues. It is off by default, but can be
+//! enabled on the command line (`-Zmir-enable-passes=+NormalizeCommutativeOps`).
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+pub struct NormalizeCommutativeOps;
+
+impl<'tcx> MirPass<'tcx> for NormalizeCommutativeOps {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running NormalizeCommutativeOps on {:?}", body.source);
+        let mut visitor = CommutativeNormalizer::new(tcx, &body.local_decls);
+        for (block, data) in body.basic_blocks.as_mut_preserves_cfg().iter_enumerated_mut() {
+            visitor.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Orders the operands of every commutative `BinaryOp` by their key: places come before
+/// constants, and places are ordered by the index of their local. Operands with the same
+/// key, like two constants or two projections of the same local, keep their order.
+///
+/// Operations on floats are left alone: whether they are commutative depends on which NaN
+/// they produce, which reordering may change.
+struct CommutativeNormalizer<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+}
+
+impl<'a, 'tcx> CommutativeNormalizer<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, local_decls: &'a LocalDecls<'tcx>) -> Self {
+        CommutativeNormalizer { tcx, local_decls }
+    }
+}
+
+fn operand_key(operand: &Operand<'_>) -> (u8, u32) {
+    match operand {
+        Operand::Copy(place) | Operand::Move(place) => (0, place.local.as_u32()),
+        Operand::Constant(_) => (1, 0),
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for CommutativeNormalizer<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        if let Rvalue::BinaryOp(op, box (ref mut lhs, ref mut rhs)) = *rvalue
+            && op.is_commutative()
+            && !lhs.ty(self.local_decls, self.tcx).is_floating_point()
+            && operand_key(lhs) > operand_key(rhs)
+        {
+            std::mem::swap(lhs, rhs);
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
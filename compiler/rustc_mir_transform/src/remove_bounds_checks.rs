@@ -0,0 +1,53 @@
+//! This pass removes the bounds checks of indexing operations, replacing each
+//! `Assert { msg: BoundsCheck, .. }` terminator with a `Goto` to its success target. It is off
+//! by default, but can be enabled on the command line (`-Zmir-enable-passes=+RemoveBoundsChecks`)
+//! to experiment with unchecked indexing.
+//!
+//! This is unsound for any out-of-bounds access, so it must never be enabled by default.
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+
+use crate::simplify;
+
+pub struct RemoveBoundsChecks;
+
+impl<'tcx> MirPass<'tcx> for RemoveBoundsChecks {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        trace!("Running RemoveBoundsChecks on {:?}", body.source);
+        let mut remover = BoundsCheckRemover { tcx, changed: false };
+        remover.visit_body(body);
+
+        // The cleanup blocks that were only reachable from a removed check are now dead.
+        if remover.changed {
+            simplify::remove_dead_blocks(body);
+        }
+    }
+}
+
+struct BoundsCheckRemover<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    changed: bool,
+}
+
+impl<'tcx> MutVisitor<'tcx> for BoundsCheckRemover<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_terminator(&mut self, terminator: &mut Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Assert { target, ref msg, .. } = terminator.kind
+            && let AssertKind::BoundsCheck { .. } = **msg
+        {
+            terminator.kind = TerminatorKind::Goto { target };
+            self.changed = true;
+        }
+        self.super_terminator(terminator, location);
+    }
+}
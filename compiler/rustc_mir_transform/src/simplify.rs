@@ -164,6 +164,25 @@ pub fn simplify(mut self) {
         }
     }
 
+    /// Only makes the edges to blocks that just contain a `Goto` go to the end of the chain of
+    /// such blocks, without the other simplifications of `simplify`. The bypassed blocks are
+    /// left in place, even if they become unreachable.
+    pub fn collapse_goto_chains(mut self) {
+        let mut changed = false;
+        for bb in self.basic_blocks.indices() {
+            if self.pred_count[bb] == 0 {
+                continue;
+            }
+
+            let mut terminator =
+                self.basic_blocks[bb].terminator.take().expect("invalid terminator state");
+            for successor in terminator.successors_mut() {
+                self.collapse_goto_chain(successor, &mut changed);
+            }
+            self.basic_blocks[bb].terminator = Some(terminator);
+        }
+    }
+
     /// This function will return `None` if
     /// * the block has statements
     /// * the block has a terminator other than `goto`
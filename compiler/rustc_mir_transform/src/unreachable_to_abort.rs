@@ -0,0 +1,83 @@
+//! A hardening pass that replaces every `Unreachable` terminator with a call to
+//! `core::intrinsics::abort`, so that reaching it traps instead of being UB. It is off by
+//! default, but can be enabled on the command line (`-Zmir-enable-passes=+UnreachableToAbort`).
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use rustc_span::sym;
+
+pub struct UnreachableToAbort;
+
+impl<'tcx> MirPass<'tcx> for UnreachableToAbort {
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        // Without `core` there is no abort intrinsic to call, so there is nothing we can do.
+        let Some(abort_def_id) = tcx.get_diagnostic_item(sym::intrinsics_abort) else {
+            return;
+        };
+
+        if !body
+            .basic_blocks
+            .iter()
+            .any(|data| matches!(data.terminator().kind, TerminatorKind::Unreachable))
+        {
+            return;
+        }
+
+        // `abort` returns `!`, so the destination of the call is never written to.
+        let never_local =
+            body.local_decls.push(LocalDecl::new(tcx.types.never, body.span).immutable());
+        let mut visitor =
+            UnreachableToAbortVisitor { tcx, abort_def_id, never_local, is_cleanup: false };
+        visitor.visit_body_preserves_cfg(body);
+    }
+}
+
+struct UnreachableToAbortVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The `DefId` of `core::intrinsics::abort`.
+    abort_def_id: rustc_hir::def_id::DefId,
+    /// A local of type `!` used as the destination of the inserted calls.
+    never_local: Local,
+    /// Whether the block currently being visited is a cleanup block.
+    is_cleanup: bool,
+}
+
+impl<'tcx> MutVisitor<'tcx> for UnreachableToAbortVisitor<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_basic_block_data(&mut self, block: BasicBlock, data: &mut BasicBlockData<'tcx>) {
+        self.is_cleanup = data.is_cleanup;
+        self.super_basic_block_data(block, data);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mut Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Unreachable = terminator.kind {
+            let span = terminator.source_info.span;
+            // `abort` never unwinds. Inside a cleanup block we must not introduce an unwind
+            // edge of our own, so we terminate instead, which is always valid there.
+            let unwind = if self.is_cleanup {
+                UnwindAction::Terminate(UnwindTerminateReason::InCleanup)
+            } else {
+                UnwindAction::Unreachable
+            };
+            terminator.kind = TerminatorKind::Call {
+                func: Operand::function_handle(self.tcx, self.abort_def_id, [], span),
+                args: Box::new([]),
+                destination: Place::from(self.never_local),
+                target: None,
+                unwind,
+                call_source: CallSource::Misc,
+                fn_span: span,
+            };
+        }
+        self.super_terminator(terminator, location);
+    }
+}
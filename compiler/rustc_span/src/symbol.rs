@@ -1045,6 +1045,7 @@
         into_iter,
         intra_doc_pointers,
         intrinsics,
+        intrinsics_abort,
         intrinsics_unaligned_volatile_load,
         intrinsics_unaligned_volatile_store,
         io_stderr,
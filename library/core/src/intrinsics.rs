@@ -934,6 +934,7 @@ pub unsafe fn drop_in_place<T: ?Sized>(to_drop: *mut T) {
     /// `SIGBUS`.  The precise behaviour is not guaranteed and not stable.
     #[rustc_safe_intrinsic]
     #[rustc_nounwind]
+    #[rustc_diagnostic_item = "intrinsics_abort"]
     pub fn abort() -> !;
 
     /// Informs the optimizer that this point in the code is not reachable,
@@ -0,0 +1,39 @@
+// Checks that building the by-move body of an async closure with the `coroutine_by_move_body`
+// query doesn't prevent the reuse of the codegen unit holding its `AsyncFnOnce` shim when
+// nothing it depends on changes. The query itself is not cached across sessions.
+
+//@ revisions: cfail1 cfail2
+//@ compile-flags: -Z query-dep-graph
+//@ edition: 2021
+//@ build-pass
+
+#![feature(async_closure, rustc_attrs)]
+#![allow(dead_code)]
+#![crate_type = "rlib"]
+#![rustc_partition_reused(module = "async_closure_by_move_body-closure", cfg = "cfail2")]
+#![rustc_partition_codegened(module = "async_closure_by_move_body-changed", cfg = "cfail2")]
+
+pub mod closure {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll};
+
+    async fn call_once(f: impl async FnOnce(i32) -> i32) -> i32 {
+        f(1).await
+    }
+
+    pub fn poll_once(cx: &mut Context<'_>) -> Poll<i32> {
+        let b = 2i32;
+        pin!(call_once(async move |a: i32| a + b)).poll(cx)
+    }
+}
+
+pub mod changed {
+    pub fn value() -> i32 {
+        #[cfg(cfail1)]
+        return 1;
+
+        #[cfg(cfail2)]
+        return 2;
+    }
+}
@@ -0,0 +1,36 @@
+//@ test-mir-pass: DeduplicateConsts
+//@ compile-flags: -Zmir-enable-passes=+GVN
+
+#![feature(custom_mir, core_intrinsics)]
+extern crate core;
+use core::intrinsics::mir::*;
+
+const A: (i32, i32) = (1, 2);
+const B: (i32, i32) = (1, 2);
+
+// `GVN` evaluates `A` and `B` to constants stored in two allocations with the same contents,
+// which are then merged into one.
+
+// EMIT_MIR deduplicate_consts.tuples.DeduplicateConsts.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn tuples() -> ((i32, i32), (i32, i32)) {
+    // CHECK-LABEL: fn tuples(
+    // CHECK: _1 = const (1_i32, 2_i32);
+    // CHECK: _2 = const (1_i32, 2_i32);
+    // CHECK: {{^}}alloc{{[0-9]+}} (size: 8, align: 4)
+    // CHECK-NOT: (size:
+    mir! {
+        let a: (i32, i32);
+        let b: (i32, i32);
+        {
+            a = A;
+            b = B;
+            RET = (a, b);
+            Return()
+        }
+    }
+}
+
+fn main() {
+    tuples();
+}
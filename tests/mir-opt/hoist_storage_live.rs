@@ -0,0 +1,41 @@
+//@ test-mir-pass: StorageLiveHoister
+
+#![feature(custom_mir, core_intrinsics)]
+extern crate core;
+use core::intrinsics::mir::*;
+
+// EMIT_MIR hoist_storage_live.revived.StorageLiveHoister.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn revived(x: i32) -> i32 {
+    // CHECK-LABEL: fn revived(
+    // CHECK: bb0: {
+    // CHECK-NEXT: StorageLive(_3);
+    // CHECK-NEXT: StorageLive(_4);
+    // CHECK-NEXT: _2 = _1;
+    // CHECK-NEXT: _3 = _2;
+    // CHECK-NEXT: StorageDead(_3);
+    // CHECK-NEXT: StorageLive(_3);
+    // CHECK-NEXT: _3 = _1;
+    // CHECK-NEXT: _4 = _3;
+    mir! {
+        let a: i32;
+        let b: i32;
+        let c: i32;
+        {
+            a = x;
+            StorageLive(b);
+            b = a;
+            StorageDead(b);
+            StorageLive(b);
+            b = x;
+            StorageLive(c);
+            c = b;
+            RET = c;
+            Return()
+        }
+    }
+}
+
+fn main() {
+    revived(1);
+}
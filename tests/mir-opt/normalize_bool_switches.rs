@@ -0,0 +1,71 @@
+//@ test-mir-pass: NormalizeBoolSwitches
+
+#![feature(custom_mir, core_intrinsics)]
+extern crate core;
+use core::intrinsics::mir::*;
+
+// EMIT_MIR normalize_bool_switches.test_false.NormalizeBoolSwitches.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn test_false(b: bool) -> u8 {
+    // CHECK-LABEL: fn test_false(
+    // CHECK: switchInt(_1) -> [1: bb2, otherwise: bb1];
+    mir! {
+        {
+            match b { false => bb1, _ => bb2 }
+        }
+        bb1 = {
+            RET = 1;
+            Return()
+        }
+        bb2 = {
+            RET = 2;
+            Return()
+        }
+    }
+}
+
+// EMIT_MIR normalize_bool_switches.test_true.NormalizeBoolSwitches.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn test_true(b: bool) -> u8 {
+    // CHECK-LABEL: fn test_true(
+    // CHECK: switchInt(_1) -> [1: bb1, otherwise: bb2];
+    mir! {
+        {
+            match b { true => bb1, _ => bb2 }
+        }
+        bb1 = {
+            RET = 1;
+            Return()
+        }
+        bb2 = {
+            RET = 2;
+            Return()
+        }
+    }
+}
+
+// EMIT_MIR normalize_bool_switches.integer.NormalizeBoolSwitches.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn integer(x: u8) -> u8 {
+    // CHECK-LABEL: fn integer(
+    // CHECK: switchInt(_1) -> [0: bb1, otherwise: bb2];
+    mir! {
+        {
+            match x { 0 => bb1, _ => bb2 }
+        }
+        bb1 = {
+            RET = 1;
+            Return()
+        }
+        bb2 = {
+            RET = 2;
+            Return()
+        }
+    }
+}
+
+fn main() {
+    test_false(true);
+    test_true(true);
+    integer(0);
+}
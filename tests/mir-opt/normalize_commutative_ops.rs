@@ -0,0 +1,35 @@
+//@ test-mir-pass: NormalizeCommutativeOps
+
+#![feature(custom_mir, core_intrinsics)]
+extern crate core;
+use core::intrinsics::mir::*;
+
+// EMIT_MIR normalize_commutative_ops.ops.NormalizeCommutativeOps.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn ops(a: i32, b: i32, c: f32, d: f32) -> i32 {
+    // CHECK-LABEL: fn ops(
+    // CHECK: bb0: {
+    // CHECK-NEXT: _5 = Add(_1, _2);
+    // CHECK-NEXT: _6 = Mul(_1, const 3_i32);
+    // CHECK-NEXT: _7 = Sub(_2, _1);
+    // CHECK-NEXT: _8 = Add(_4, _3);
+    // CHECK-NEXT: _0 = BitXor(_5, _6);
+    mir! {
+        let x: i32;
+        let y: i32;
+        let z: i32;
+        let w: f32;
+        {
+            x = b + a;
+            y = 3_i32 * a;
+            z = b - a;
+            w = d + c;
+            RET = x ^ y;
+            Return()
+        }
+    }
+}
+
+fn main() {
+    ops(2, 5, 1.0, 2.0);
+}
@@ -0,0 +1,87 @@
+// Scaffolding shared by the `mir-*` tests, which include it with `#[path]`. They must declare
+// `extern crate` for `rustc_driver`, `rustc_hir`, `rustc_interface`, `rustc_middle`,
+// `rustc_session` and `rustc_span`.
+
+use std::path::PathBuf;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_interface::interface;
+use rustc_middle::mir::{Body, Local, VarDebugInfoContents};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::{Input, Options, OutputType, OutputTypes};
+use rustc_span::{FileName, Symbol};
+
+/// Compiles `src` with the sysroot given as the first argument of the test, and calls `f` once
+/// the crate has been analyzed.
+pub fn run_compiler(src: &str, f: impl for<'tcx> FnOnce(TyCtxt<'tcx>) + Send) {
+    run_compiler_with(src, |_| {}, f)
+}
+
+/// Like `run_compiler`, but lets `configure` change the configuration of the compiler first,
+/// e.g. to set options or to override queries.
+pub fn run_compiler_with(
+    src: &str,
+    configure: impl FnOnce(&mut interface::Config),
+    f: impl for<'tcx> FnOnce(TyCtxt<'tcx>) + Send,
+) {
+    let args: Vec<String> = std::env::args().collect();
+    let sysroot = PathBuf::from(args.get(1).expect("expected sysroot"));
+
+    let mut opts = Options::default();
+    opts.output_types = OutputTypes::new(&[(OutputType::Metadata, None)]);
+    opts.maybe_sysroot = Some(sysroot);
+
+    let name = FileName::anon_source_code(src);
+    let input = Input::Str { name, input: src.to_string() };
+
+    let mut config = interface::Config {
+        opts,
+        crate_cfg: Default::default(),
+        crate_check_cfg: Default::default(),
+        input,
+        output_file: None,
+        output_dir: None,
+        ice_file: None,
+        file_loader: None,
+        locale_resources: &[],
+        lint_caps: Default::default(),
+        psess_created: None,
+        hash_untracked_state: None,
+        register_lints: None,
+        override_queries: None,
+        make_codegen_backend: None,
+        registry: rustc_driver::diagnostics_registry(),
+        using_internal_features: std::sync::Arc::default(),
+        expanded_args: Default::default(),
+    };
+    configure(&mut config);
+
+    interface::run_compiler(config, |compiler| {
+        compiler.enter(|queries| {
+            queries.global_ctxt().unwrap().enter(|tcx| {
+                tcx.analysis(()).unwrap();
+                f(tcx)
+            })
+        });
+    });
+}
+
+/// Returns the function of the local crate named `name`.
+pub fn find_fn(tcx: TyCtxt<'_>, name: Symbol) -> DefId {
+    tcx.hir()
+        .body_owners()
+        .find(|&def_id| {
+            tcx.def_kind(def_id) == DefKind::Fn && tcx.item_name(def_id.to_def_id()) == name
+        })
+        .unwrap()
+        .to_def_id()
+}
+
+/// Returns the local of the user variable `name` of `body`.
+pub fn local_named(body: &Body<'_>, name: &str) -> Local {
+    let name = Symbol::intern(name);
+    let info = body.var_debug_info.iter().find(|info| info.name == name).unwrap();
+    let VarDebugInfoContents::Place(place) = info.value else { panic!("{name} is a constant") };
+    place.as_local().unwrap()
+}
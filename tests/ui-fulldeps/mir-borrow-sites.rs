@@ -0,0 +1,53 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `BorrowSites` collects the shared and mutable borrows of a body, and not the raw
+// borrows.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::borrow_sites::BorrowSites;
+use rustc_middle::mir::{BorrowKind, Local};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn borrows(mut a: u32, b: u32) -> u32 {
+        let shared = &b;
+        let value = *shared;
+        let unique = &mut a;
+        *unique ^= value;
+        let raw = std::ptr::addr_of!(a);
+        a
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let borrows = find_fn(tcx, Symbol::intern("borrows")).expect_local();
+        let body = tcx.mir_promoted(borrows).0.borrow();
+
+        let sites = BorrowSites::collect(&body);
+        assert_eq!(sites.len(), 2);
+        assert_eq!(sites[0].place.as_local(), Some(Local::from_u32(2)));
+        assert_eq!(sites[0].kind, BorrowKind::Shared);
+        assert_eq!(sites[1].place.as_local(), Some(Local::from_u32(1)));
+        assert!(matches!(sites[1].kind, BorrowKind::Mut { .. }));
+        assert!(sites[0].location < sites[1].location);
+    });
+}
@@ -0,0 +1,59 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `for_each_call_abi` visits every call of a body with the ABI of its callee, for
+// calls of function items and calls through function pointers alike.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+extern crate rustc_target;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::call_abi::for_each_call_abi;
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub extern "C" fn foreign(x: u32) -> u32 {
+        x
+    }
+
+    pub fn native(x: u32) -> u32 {
+        x
+    }
+
+    pub fn caller(f: extern "C" fn(u32) -> u32) -> u32 {
+        native(foreign(f(1)))
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let caller = find_fn(tcx, Symbol::intern("caller"));
+        let body = tcx.mir_promoted(caller.expect_local()).0.borrow();
+
+        let mut abis = Vec::new();
+        for_each_call_abi(tcx, &body, |_, args, abi, _| {
+            assert_eq!(args.len(), 1);
+            abis.push(abi);
+        });
+
+        // The calls are nested, so the innermost one comes first.
+        let c = Some(Abi::C { unwind: false });
+        assert_eq!(abis, [c, c, Some(Abi::Rust)]);
+    });
+}
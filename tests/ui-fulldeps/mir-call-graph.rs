@@ -0,0 +1,60 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `CallGraphEdges` resolves the callee of a call to a function item, and records
+// the callee of a call through a function pointer as unknown, and that `CalledIntrinsics` only
+// finds the calls to intrinsics.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::call_graph::{CallGraphEdges, CalledIntrinsics};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(core_intrinsics)]
+    #![allow(internal_features)]
+
+    #[inline(never)]
+    pub fn callee() {}
+
+    pub fn caller(f: fn()) {
+        callee();
+        f();
+    }
+
+    pub fn intrinsic(x: u32) -> u32 {
+        callee();
+        core::intrinsics::black_box(x)
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let callee = find_fn(tcx, Symbol::intern("callee"));
+        let caller = find_fn(tcx, Symbol::intern("caller"));
+
+        let edges = CallGraphEdges::collect(tcx.optimized_mir(caller));
+        assert_eq!(edges, [(caller, Some(callee)), (caller, None)]);
+
+        let intrinsic = find_fn(tcx, Symbol::intern("intrinsic"));
+        let calls = CalledIntrinsics::collect(tcx, tcx.optimized_mir(intrinsic));
+        assert_eq!(calls.len(), 1);
+        assert_eq!(tcx.item_name(calls[0].0), Symbol::intern("black_box"));
+    });
+}
@@ -0,0 +1,56 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `CaptureFieldUses` finds the upvars used by a closure body, through the reference
+// to its capture struct, and nothing in a body that only uses its arguments.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+extern crate rustc_target;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::capture_field_uses::CaptureFieldUses;
+use rustc_span::Symbol;
+use rustc_target::abi::FieldIdx;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn choose(a: u32, b: u32) -> impl Fn(bool) -> u32 {
+        move |flag| if flag { a } else { b }
+    }
+
+    pub fn plain(a: u32, b: u32) -> u32 {
+        a ^ b
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let closure = tcx
+            .hir()
+            .body_owners()
+            .find(|&def_id| tcx.is_closure_like(def_id.to_def_id()))
+            .unwrap();
+        let body = tcx.mir_promoted(closure).0.borrow();
+        let fields: Vec<_> = CaptureFieldUses::collect(&body).iter().collect();
+        assert_eq!(fields, [FieldIdx::from_u32(0), FieldIdx::from_u32(1)]);
+
+        // `a` is the first argument of `plain`, but it is never projected.
+        let plain = find_fn(tcx, Symbol::intern("plain")).expect_local();
+        let body = tcx.mir_promoted(plain).0.borrow();
+        assert!(CaptureFieldUses::collect(&body).is_empty());
+    });
+}
@@ -0,0 +1,56 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `ConstAllocs` follows a constant reference to a string slice down to the bytes of
+// the string, and finds nothing for a constant integer.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::const_allocs::ConstAllocs;
+use rustc_middle::mir::interpret::GlobalAlloc;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn nested() -> &'static &'static str {
+        &"hi"
+    }
+
+    pub fn integer() -> u32 {
+        7
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        // The reference points to the wide pointer to the string, which points to its bytes.
+        let body = tcx.optimized_mir(find_fn(tcx, Symbol::intern("nested")));
+        let allocs = ConstAllocs::collect(tcx, body);
+        assert_eq!(allocs.len(), 2);
+        assert!(allocs.iter().any(|&alloc_id| match tcx.global_alloc(alloc_id) {
+            GlobalAlloc::Memory(alloc) => {
+                let alloc = alloc.inner();
+                alloc.inspect_with_uninit_and_ptr_outside_interpreter(0..alloc.len()) == b"hi"
+            }
+            _ => false,
+        }));
+
+        let body = tcx.optimized_mir(find_fn(tcx, Symbol::intern("integer")));
+        assert!(ConstAllocs::collect(tcx, body).is_empty());
+    });
+}
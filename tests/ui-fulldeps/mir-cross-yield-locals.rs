@@ -0,0 +1,54 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `CrossYieldLocals` finds a local assigned before a yield and read after it, and
+// not a local that is only used before the yield.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::cross_yield::CrossYieldLocals;
+
+use driver::{local_named, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(coroutines, coroutine_trait, stmt_expr_attributes)]
+
+    use std::ops::Coroutine;
+
+    pub fn coroutine(a: u32) -> impl Coroutine<Yield = u32, Return = u32> {
+        #[coroutine]
+        move || {
+            let x = a;
+            let y = 7;
+            yield y;
+            x
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let coroutine =
+            tcx.hir().body_owners().find(|&def_id| tcx.is_coroutine(def_id.to_def_id())).unwrap();
+
+        // The body before the coroutine transform, which still has the `Yield` terminator.
+        let body = tcx.mir_promoted(coroutine).0.borrow();
+        let cross_yield = CrossYieldLocals::compute(&body);
+        assert!(cross_yield.contains(local_named(&body, "x")));
+        assert!(!cross_yield.contains(local_named(&body, "y")));
+    });
+}
@@ -0,0 +1,90 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `DeadStores` reports a store that is overwritten before being read, but not a
+// store followed by a partial write, nor a store to a borrowed local.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::dead_stores::DeadStores;
+use rustc_middle::mir::{BasicBlock, Location};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn overwritten(a: i32) -> i32 {
+        mir! {
+            let x: i32;
+            {
+                x = a;
+                x = 2_i32;
+                RET = x;
+                Return()
+            }
+        }
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn partial(a: i32) -> i32 {
+        mir! {
+            let t: (i32, i32);
+            {
+                t = (a, a);
+                t.0 = 2_i32;
+                RET = t.1;
+                Return()
+            }
+        }
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn borrowed(a: i32) -> i32 {
+        mir! {
+            let x: i32;
+            let r: &i32;
+            {
+                x = a;
+                r = &x;
+                x = 2_i32;
+                RET = x;
+                Return()
+            }
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let first = Location { block: BasicBlock::from_u32(0), statement_index: 0 };
+        assert_eq!(compute(tcx, "overwritten"), [first]);
+        assert!(compute(tcx, "partial").is_empty());
+        assert!(compute(tcx, "borrowed").is_empty());
+    });
+}
+
+/// Returns the dead stores of the built body of the function `name`.
+fn compute(tcx: TyCtxt<'_>, name: &str) -> Vec<Location> {
+    let def_id = find_fn(tcx, Symbol::intern(name)).expect_local();
+    DeadStores::compute(&tcx.mir_promoted(def_id).0.borrow())
+}
@@ -0,0 +1,58 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `DropFlagUses` finds the drop flag of a conditionally moved local once drops are
+// elaborated, with the assignments and the read of the flag.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::drop_flag_uses::DropFlagUses;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn maybe_drop(s: String, c: bool) {
+        if c {
+            drop(s);
+        }
+    }
+
+    pub fn no_flags(s: String) -> usize {
+        s.len()
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("maybe_drop")).expect_local();
+        let body = tcx.mir_drops_elaborated_and_const_checked(def_id).borrow();
+        let (flags, uses) = DropFlagUses::collect(&body);
+        assert!(!flags.is_empty());
+        for flag in flags.iter() {
+            let mut contexts = uses.iter().filter(|&&(local, ..)| local == flag);
+            assert!(contexts.clone().any(|(_, context, _)| context.is_mutating_use()));
+            assert!(contexts.any(|(_, context, _)| !context.is_mutating_use()));
+        }
+
+        let def_id = find_fn(tcx, Symbol::intern("no_flags")).expect_local();
+        let body = tcx.mir_drops_elaborated_and_const_checked(def_id).borrow();
+        let (flags, uses) = DropFlagUses::collect(&body);
+        assert!(flags.is_empty());
+        assert!(uses.is_empty());
+    });
+}
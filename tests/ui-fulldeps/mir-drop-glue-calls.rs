@@ -0,0 +1,67 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `DropGlueCalls` finds the calls of `drop_in_place` and through function pointers
+// of a body, and the call of a `Drop` impl in drop glue, but not the ordinary calls.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_hir::LangItem;
+use rustc_middle::mir::drop_glue_calls::{DropGlueCall, DropGlueCalls};
+use rustc_middle::ty::InstanceKind;
+use rustc_span::Symbol;
+
+use driver::{find_fn, local_named, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub struct S;
+
+    impl Drop for S {
+        fn drop(&mut self) {}
+    }
+
+    pub fn make() -> S {
+        S
+    }
+
+    pub fn calls(p: *mut String, f: fn()) {
+        make();
+        unsafe { std::ptr::drop_in_place(p) };
+        f();
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("calls")).expect_local();
+        let body = tcx.mir_promoted(def_id).0.borrow();
+        let string = body.local_decls[local_named(&body, "p")].ty.builtin_deref(true).unwrap();
+
+        let calls: Vec<_> =
+            DropGlueCalls::collect(tcx, &body).into_iter().map(|(_, call)| call).collect();
+        assert_eq!(calls, [DropGlueCall::DropInPlace(string), DropGlueCall::Unknown]);
+
+        // The drop glue of `S` calls its `Drop` impl.
+        let make = find_fn(tcx, Symbol::intern("make"));
+        let s = tcx.fn_sig(make).instantiate_identity().output().skip_binder();
+        let drop_in_place = tcx.require_lang_item(LangItem::DropInPlace, None);
+        let glue = tcx.instance_mir(InstanceKind::DropGlue(drop_in_place, Some(s)));
+        let calls: Vec<_> =
+            DropGlueCalls::collect(tcx, glue).into_iter().map(|(_, call)| call).collect();
+        assert_eq!(calls, [DropGlueCall::DropImpl(s)]);
+    });
+}
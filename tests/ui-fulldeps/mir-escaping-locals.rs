@@ -0,0 +1,70 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `EscapingLocals` traces a reference passed to a call back to the borrowed local
+// through a copy, and ignores the locals whose address is taken but never passed.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::escaping_locals::EscapingLocals;
+use rustc_middle::mir::Local;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    pub fn read(r: &i32) -> i32 {
+        *r
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn escape(a: i32) -> i32 {
+        mir! {
+            let x: i32;
+            let y: i32;
+            let r: &i32;
+            let s: &i32;
+            let p: *const i32;
+            {
+                x = a;
+                y = a;
+                r = &x;
+                s = r;
+                p = &raw const y;
+                Call(RET = read(Move(s)), ReturnTo(done), UnwindContinue())
+            }
+            done = {
+                Return()
+            }
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("escape")).expect_local();
+        let body = tcx.mir_promoted(def_id).0.borrow();
+
+        // Only `x`, borrowed by `r` and passed to `read` through `s`, escapes.
+        let escaping = EscapingLocals::collect(&body);
+        assert_eq!(escaping.iter().collect::<Vec<_>>(), [Local::from_u32(2)]);
+    });
+}
@@ -0,0 +1,93 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that a `MutVisitor` can replace `NullOp::UbChecks` with `const true` through the
+// `visit_nullary_op` hook.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::{
+    Body, Const, ConstOperand, Location, NullOp, Operand, Rvalue, StatementKind,
+};
+use rustc_middle::ty::{Ty, TyCtxt};
+use rustc_span::{Symbol, DUMMY_SP};
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(core_intrinsics)]
+    #![allow(internal_features)]
+
+    pub fn checks() -> bool {
+        std::intrinsics::ub_checks()
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let checks = find_fn(tcx, Symbol::intern("checks"));
+        let mut body: Body<'_> = tcx.optimized_mir(checks).clone();
+        assert_eq!(rvalues(&body).len(), 1);
+        assert!(matches!(rvalues(&body)[0], Rvalue::NullaryOp(NullOp::UbChecks, _)));
+
+        let mut visitor = ForceUbChecks { tcx, replaced: 0 };
+        visitor.visit_body(&mut body);
+        assert_eq!(visitor.replaced, 1);
+
+        let rvalues = rvalues(&body);
+        assert_eq!(rvalues.len(), 1);
+        let Rvalue::Use(Operand::Constant(constant)) = &rvalues[0] else { panic!() };
+        assert_eq!(constant.const_.try_to_bool(), Some(true));
+    });
+}
+
+/// Returns the rvalues assigned by `body`.
+fn rvalues<'tcx>(body: &Body<'tcx>) -> Vec<Rvalue<'tcx>> {
+    let statements = body.basic_blocks.iter().flat_map(|block| &block.statements);
+    statements
+        .filter_map(|statement| match &statement.kind {
+            StatementKind::Assign(assign) => Some(assign.1.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Replaces `UbChecks` with `const true`.
+struct ForceUbChecks<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    replaced: usize,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ForceUbChecks<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_nullary_op(
+        &mut self,
+        op: &mut NullOp<'tcx>,
+        ty: &mut Ty<'tcx>,
+        location: Location,
+    ) -> Option<Operand<'tcx>> {
+        self.super_nullary_op(op, ty, location);
+        let NullOp::UbChecks = op else { return None };
+        self.replaced += 1;
+        let const_ = Const::from_bool(self.tcx, true);
+        Some(Operand::Constant(Box::new(ConstOperand { span: DUMMY_SP, user_ty: None, const_ })))
+    }
+}
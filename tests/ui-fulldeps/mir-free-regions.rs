@@ -0,0 +1,55 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `FreeRegions` finds nothing in an optimized body, whose regions are erased, and
+// finds a free region put back in the type of one of its locals.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::free_regions::{FreeRegions, RegionSite};
+use rustc_middle::mir::visit::TyContext;
+use rustc_middle::mir::{Body, Local};
+use rustc_middle::ty::Ty;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn read(x: &u32) -> u32 {
+        *x
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let body = tcx.optimized_mir(find_fn(tcx, Symbol::intern("read")));
+        assert!(FreeRegions::collect(tcx, body).is_empty());
+
+        let mut body: Body<'_> = body.clone();
+        let x = Local::from_u32(1);
+        let re_static = tcx.lifetimes.re_static;
+        body.local_decls[x].ty = Ty::new_imm_ref(tcx, re_static, tcx.types.u32);
+
+        let regions = FreeRegions::collect(tcx, &body);
+        assert_eq!(regions.len(), 1);
+        let sites = &regions[&re_static];
+        assert_eq!(sites.len(), 1);
+        assert!(
+            matches!(sites[0], RegionSite::Ty(TyContext::LocalDecl { local, .. }) if local == x)
+        );
+    });
+}
@@ -0,0 +1,67 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks the costs computed by `InlineCost` for the blocks of a small body, with the default
+// weights and with unit weights.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::inline_cost::{InlineCost, InlineCostWeights};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    pub fn id(x: i32) -> i32 {
+        x
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn cost(a: i32, b: i32) -> i32 {
+        mir! {
+            let x: i32;
+            {
+                StorageLive(x);
+                x = a + b;
+                Call(RET = id(x), ReturnTo(done), UnwindContinue())
+            }
+            done = {
+                StorageDead(x);
+                Return()
+            }
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("cost")).expect_local();
+        let body = tcx.mir_promoted(def_id).0.borrow();
+
+        // The storage markers are free, the addition costs a statement and its two operands,
+        // and the call costs a call, its callee and its argument.
+        let costs = InlineCost::compute(&body, InlineCostWeights::default());
+        assert_eq!(costs.raw, [(5 + 2, 25 + 2), (0, 5)]);
+
+        let unit = InlineCostWeights { statement: 1, terminator: 1, call: 1, operand: 1 };
+        assert_eq!(InlineCost::total(&body, unit), 3 + 3 + 1);
+    });
+}
@@ -0,0 +1,105 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `InlineSpanRewriter` moves every statement and terminator of a callee body into
+// the inlined scope, and keeps their original spans, except for the dummy ones, which are
+// replaced with the span of the call site.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_mir_transform;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::visit::{MutVisitor, Visitor};
+use rustc_middle::mir::{
+    Body, ClearCrossCrate, SourceInfo, SourceScopeData, OUTERMOST_SOURCE_SCOPE,
+};
+use rustc_middle::ty::Instance;
+use rustc_mir_transform::inline_spans::InlineSpanRewriter;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn callee(x: u32) -> u32 {
+        let y = x.wrapping_add(1);
+        y.wrapping_mul(2)
+    }
+
+    pub fn caller() -> u32 {
+        callee(3)
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let callee = find_fn(tcx, Symbol::intern("callee"));
+        let call_site_span = tcx.def_span(find_fn(tcx, Symbol::intern("caller")));
+
+        let mut body: Body<'_> = tcx.optimized_mir(callee).clone();
+        let inlined_scope = body.source_scopes.push(SourceScopeData {
+            span: call_site_span,
+            parent_scope: Some(OUTERMOST_SOURCE_SCOPE),
+            inlined: Some((Instance::mono(tcx, callee), call_site_span)),
+            inlined_parent_scope: None,
+            local_data: ClearCrossCrate::Clear,
+        });
+
+        let before = source_infos(&body);
+        let statement_spans = statement_source_infos(&body);
+        assert!(statement_spans.iter().any(|source_info| !source_info.span.is_dummy()));
+
+        InlineSpanRewriter::new(tcx, call_site_span, inlined_scope).visit_body(&mut body);
+
+        let after = source_infos(&body);
+        assert_eq!(before.len(), after.len());
+        for (before, after) in before.iter().zip(&after) {
+            assert_eq!(after.scope, inlined_scope);
+            if before.span.is_dummy() {
+                assert_eq!(after.span, call_site_span);
+            } else {
+                assert_eq!(after.span, before.span);
+            }
+        }
+
+        // The statements are the ones that debuggers step through.
+        let statements = statement_source_infos(&body);
+        for (before, after) in statement_spans.iter().zip(&statements) {
+            assert_eq!(after.scope, inlined_scope);
+            assert!(before.span.is_dummy() || after.span == before.span);
+        }
+    });
+}
+
+/// Returns every `SourceInfo` of `body`, in visiting order.
+fn source_infos(body: &Body<'_>) -> Vec<SourceInfo> {
+    struct SourceInfos(Vec<SourceInfo>);
+
+    impl<'tcx> Visitor<'tcx> for SourceInfos {
+        fn visit_source_info(&mut self, source_info: &SourceInfo) {
+            self.0.push(*source_info);
+        }
+    }
+
+    let mut visitor = SourceInfos(Vec::new());
+    visitor.visit_body(body);
+    visitor.0
+}
+
+/// Returns the `SourceInfo` of every statement of `body`.
+fn statement_source_infos(body: &Body<'_>) -> Vec<SourceInfo> {
+    let statements = body.basic_blocks.iter().flat_map(|block| &block.statements);
+    statements.map(|statement| statement.source_info).collect()
+}
@@ -0,0 +1,109 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Instruments the calls of a crate with `CallInstrumenter` right after MIR building, and
+// checks with `-Zvalidate-mir` that the bodies stay valid through the rest of the MIR
+// pipeline, including calls that move arguments while a value that needs dropping is live, and
+// that instrumenting the calls twice is the same as doing it once.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_mir_transform;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_middle::mir::{TerminatorKind, UnwindAction, UnwindTerminateReason};
+use rustc_middle::query::queries::mir_promoted::ProvidedValue;
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::util::Providers;
+use rustc_mir_transform::instrument_calls::CallInstrumenter;
+use rustc_session::Session;
+use rustc_span::{sym, Symbol};
+
+use driver::{find_fn, run_compiler_with};
+
+fn main() {
+    let src = r#"
+    pub fn log_call() {}
+
+    fn consume(s: String) -> usize {
+        s.len()
+    }
+
+    fn caller(s: String, t: String) -> usize {
+        let kept = String::from("kept");
+        consume(s) + consume(t) + kept.len()
+    }
+
+    fn main() {
+        caller(String::new(), String::new());
+    }
+    "#;
+
+    run_compiler_with(
+        src,
+        |config| {
+            config.opts.unstable_opts.validate_mir = true;
+            config.override_queries = Some(override_queries);
+        },
+        |tcx| {
+            let logger = find_fn(tcx, Symbol::intern("log_call"));
+            check_instrumented(tcx, find_fn(tcx, Symbol::intern("caller")), logger);
+            check_instrumented(tcx, find_fn(tcx, sym::main), logger);
+        },
+    );
+}
+
+fn override_queries(_session: &Session, local: &mut Providers) {
+    local.mir_promoted = mir_promoted;
+}
+
+fn mir_promoted<'tcx>(tcx: TyCtxt<'tcx>, def: LocalDefId) -> ProvidedValue<'tcx> {
+    let mut providers = Providers::default();
+    rustc_mir_transform::provide(&mut providers);
+    let (body, promoted) = (providers.mir_promoted)(tcx, def);
+    if tcx.def_kind(def) != DefKind::Fn {
+        return (body, promoted);
+    }
+    let mut body = body.steal();
+    let logger = find_fn(tcx, Symbol::intern("log_call"));
+    // Instrumenting twice must not call `logger` twice before a call.
+    CallInstrumenter { logger }.instrument(tcx, &mut body);
+    CallInstrumenter { logger }.instrument(tcx, &mut body);
+    (tcx.alloc_steal_mir(body), promoted)
+}
+
+/// Checks that every call of the optimized body of `def_id` is preceded by a call to `logger`
+/// that can't unwind.
+fn check_instrumented(tcx: TyCtxt<'_>, def_id: DefId, logger: DefId) {
+    let body = tcx.optimized_mir(def_id);
+    let mut logger_calls = 0;
+    let mut other_calls = 0;
+    for block in body.basic_blocks.iter() {
+        let TerminatorKind::Call { func, target, unwind, .. } = &block.terminator().kind else {
+            continue;
+        };
+        if func.const_fn_def().is_some_and(|(callee, _)| callee == logger) {
+            logger_calls += 1;
+            assert!(matches!(unwind, UnwindAction::Terminate(UnwindTerminateReason::Abi)));
+            let target = &body.basic_blocks[target.unwrap()];
+            assert!(target.statements.is_empty());
+            assert!(matches!(target.terminator().kind, TerminatorKind::Call { .. }));
+        } else {
+            other_calls += 1;
+        }
+    }
+    assert!(other_calls > 0);
+    assert_eq!(logger_calls, other_calls);
+}
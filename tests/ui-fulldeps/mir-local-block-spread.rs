@@ -0,0 +1,64 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks the number of blocks that `LocalBlockSpread` finds for each local, with and without
+// the storage markers.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::local_block_spread::LocalBlockSpread;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    pub fn id(x: i32) -> i32 {
+        x
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn spread(a: i32, b: i32) -> i32 {
+        mir! {
+            let x: i32;
+            {
+                StorageLive(x);
+                x = a + b;
+                Call(RET = id(x), ReturnTo(done), UnwindContinue())
+            }
+            done = {
+                StorageDead(x);
+                Return()
+            }
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("spread")).expect_local();
+        let body = tcx.mir_promoted(def_id).0.borrow();
+
+        // The return place is written by the call and read by `Return`, and `x` is only
+        // mentioned by the second block through its `StorageDead`.
+        assert_eq!(LocalBlockSpread::compute(&body, true).raw, [2, 1, 1, 2]);
+        assert_eq!(LocalBlockSpread::compute(&body, false).raw, [2, 1, 1, 1]);
+    });
+}
@@ -0,0 +1,66 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `LocalUseRange` spans from the assignment of a variable to its last read, and that
+// a variable that is never used has no range.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::local_use_range::LocalUseRange;
+use rustc_middle::mir::{Location, Operand, Rvalue, StatementKind, RETURN_PLACE};
+use rustc_span::Symbol;
+
+use driver::{find_fn, local_named, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn ranges(a: u32) -> u32 {
+        let declared: u32;
+        let x = a;
+        x
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let ranges = find_fn(tcx, Symbol::intern("ranges")).expect_local();
+
+        // The body as built, before `x` is propagated.
+        let body = tcx.mir_promoted(ranges).0.borrow();
+        let x = local_named(&body, "x");
+        let mut assigned = None;
+        let mut returned = None;
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                let location = Location { block, statement_index };
+                let StatementKind::Assign(assign) = &statement.kind else { continue };
+                match &**assign {
+                    (place, _) if place.as_local() == Some(x) => assigned = Some(location),
+                    (place, Rvalue::Use(Operand::Copy(read)))
+                        if place.local == RETURN_PLACE && read.as_local() == Some(x) =>
+                    {
+                        returned = Some(location)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let ranges = LocalUseRange::compute(&body);
+        assert_eq!(ranges[x], Some((assigned.unwrap(), returned.unwrap())));
+        assert_eq!(ranges[local_named(&body, "declared")], None);
+    });
+}
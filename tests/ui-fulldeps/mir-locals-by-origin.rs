@@ -0,0 +1,45 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `LocalsByOrigin` finds the user variables of a built body, arguments included,
+// and none of its temporaries.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::locals_by_origin::LocalsByOrigin;
+use rustc_middle::mir::LocalInfo;
+use rustc_span::Symbol;
+
+use driver::{find_fn, local_named, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn sum(a: u32, b: u32) -> u32 {
+        let x = a.wrapping_add(b);
+        x
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("sum")).expect_local();
+        let body = tcx.mir_promoted(def_id).0.borrow();
+
+        let users = LocalsByOrigin::collect(&body, |info| matches!(info, LocalInfo::User(_)));
+        let expected = ["a", "b", "x"].map(|name| local_named(&body, name));
+        assert_eq!(users, expected);
+    });
+}
@@ -0,0 +1,66 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `MinLengthConstraints` records one `min_length` per slice pattern binding the
+// elements of a slice, and the length of an array destructured by a pattern.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::min_length::MinLengthConstraints;
+use rustc_middle::mir::Local;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn slice(s: &[u32]) -> u32 {
+        match s {
+            [a] => *a,
+            [a, b, ..] => a ^ b,
+            _ => 0,
+        }
+    }
+
+    pub fn array(a: [u32; 3]) -> u32 {
+        let [x, _, z] = a;
+        x ^ z
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        // The slice is `*_1`, the array is `_1`.
+        assert_eq!(constraints(tcx, "slice"), [(Local::from_u32(1), 1, vec![1, 2])]);
+        assert_eq!(constraints(tcx, "array"), [(Local::from_u32(1), 0, vec![3])]);
+    });
+}
+
+/// Returns the sorted `min_length`s of the places of the function `name` as built, with the
+/// local and the number of projections of each place.
+fn constraints(tcx: TyCtxt<'_>, name: &str) -> Vec<(Local, usize, Vec<u64>)> {
+    let def_id = find_fn(tcx, Symbol::intern(name)).expect_local();
+    let body = tcx.mir_promoted(def_id).0.borrow();
+    MinLengthConstraints::collect(&body)
+        .into_iter()
+        .map(|(place, min_lengths)| {
+            let mut min_lengths: Vec<u64> = min_lengths.into_iter().collect();
+            min_lengths.sort();
+            (place.local, place.projection.len(), min_lengths)
+        })
+        .collect()
+}
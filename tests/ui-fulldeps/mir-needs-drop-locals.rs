@@ -0,0 +1,43 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `NeedsDropLocals` reports the locals whose type has drop glue, including the ones
+// of a generic type, unless the `ParamEnv` proves that the type is `Copy`.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::needs_drop_locals::NeedsDropLocals;
+use rustc_middle::mir::Local;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn arguments<T, U: Copy>(s: String, n: u32, t: T, u: U) {}
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let arguments = find_fn(tcx, Symbol::intern("arguments"));
+        let body = tcx.optimized_mir(arguments);
+
+        let locals = NeedsDropLocals::compute(tcx, tcx.param_env(arguments), body);
+        let needs_drop: Vec<bool> =
+            (1..=4).map(|local| locals.contains(Local::from_u32(local))).collect();
+        assert_eq!(needs_drop, [true, false, true, false]);
+    });
+}
@@ -0,0 +1,59 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `PanicFreedom` finds the bounds checks, the division checks and the explicit
+// panics of a body, and nothing in a body that can't panic.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::panic_freedom::{PanicFreedom, PanicSource};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn free(a: u32) -> u32 {
+        a ^ 1
+    }
+
+    pub fn index(a: [u32; 4], i: usize) -> u32 {
+        a[i]
+    }
+
+    pub fn divide(a: u32, b: u32) -> u32 {
+        a / b
+    }
+
+    pub fn explicit() {
+        panic!("boom")
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        assert_eq!(check(tcx, "free"), None);
+        assert_eq!(check(tcx, "index"), Some(PanicSource::BoundsCheck));
+        assert_eq!(check(tcx, "divide"), Some(PanicSource::Overflow));
+        assert!(matches!(check(tcx, "explicit"), Some(PanicSource::ExplicitPanic(_))));
+    });
+}
+
+/// Returns the first source of panics of the optimized body of the function `name`.
+fn check(tcx: TyCtxt<'_>, name: &str) -> Option<PanicSource> {
+    PanicFreedom::check(tcx, tcx.optimized_mir(find_fn(tcx, Symbol::intern(name))))
+}
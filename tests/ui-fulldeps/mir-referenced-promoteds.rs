@@ -0,0 +1,67 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `ReferencedPromoteds` finds every promoted of a body that uses them, and nothing
+// once the body stops using them.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::referenced_promoteds::ReferencedPromoteds;
+use rustc_middle::mir::{Body, Const, Operand, Rvalue, StatementKind};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn nested() -> &'static &'static u32 {
+        &&1
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let def_id = find_fn(tcx, Symbol::intern("nested")).expect_local();
+        let (body, promoted) = tcx.mir_promoted(def_id);
+        let body = body.borrow();
+        let promoted = promoted.borrow();
+
+        // Every promoted of `&&1` is used, by the body or by the promoted that contains it.
+        assert!(!promoted.is_empty());
+        let referenced = ReferencedPromoteds::collect(&body, &promoted);
+        assert_eq!(referenced.count(), promoted.len());
+
+        let mut body: Body<'_> = body.clone();
+        for data in body.basic_blocks.as_mut() {
+            for statement in &mut data.statements {
+                if uses_promoted(&statement.kind) {
+                    statement.make_nop();
+                }
+            }
+        }
+        assert!(ReferencedPromoteds::collect(&body, &promoted).is_empty());
+    });
+}
+
+/// Returns whether `kind` assigns a promoted constant.
+fn uses_promoted(kind: &StatementKind<'_>) -> bool {
+    let StatementKind::Assign(assign) = kind else { return false };
+    let Rvalue::Use(Operand::Constant(constant)) = &assign.1 else { return false };
+    match constant.const_ {
+        Const::Unevaluated(uv, _) => uv.promoted.is_some(),
+        _ => false,
+    }
+}
@@ -0,0 +1,112 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `RegionRefresher` gives each erased region of an optimized body its own
+// placeholder region, and that it creates exactly one per erased occurrence.
+
+#![feature(rustc_private)]
+
+extern crate rustc_data_structures;
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def::DefKind;
+use rustc_middle::mir::region_refresher::RegionRefresher;
+use rustc_middle::mir::visit::{MutVisitor, TyContext, Visitor};
+use rustc_middle::mir::{ConstOperand, Location};
+use rustc_middle::ty::{self, GenericArgsRef, Ty, TyCtxt, TypeFoldable};
+
+use driver::run_compiler;
+
+fn main() {
+    let src = r#"
+    pub fn longest<'a>(a: &'a str, b: &'a str) -> &'a str {
+        if a.len() > b.len() { a } else { b }
+    }
+
+    pub fn first<'a, T>(items: &'a [&'a T]) -> Option<&'a &'a T> {
+        let items: &[&T] = items;
+        items.first()
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        for def_id in tcx.hir().body_owners() {
+            if tcx.def_kind(def_id) == DefKind::Fn {
+                check_body(tcx, def_id.to_def_id());
+            }
+        }
+    });
+}
+
+fn check_body<'tcx>(tcx: TyCtxt<'tcx>, def_id: rustc_span::def_id::DefId) {
+    let mut body = tcx.optimized_mir(def_id).clone();
+
+    let mut before = Regions { tcx, regions: Vec::new() };
+    before.visit_body(&body);
+    assert!(before.regions.iter().all(|region| region.is_erased()));
+
+    let mut refresher = RegionRefresher::new(tcx);
+    refresher.visit_body(&mut body);
+    assert_eq!(refresher.fresh_regions() as usize, before.regions.len());
+
+    let mut after = Regions { tcx, regions: Vec::new() };
+    after.visit_body(&body);
+    assert_eq!(after.regions.len(), before.regions.len());
+    assert!(after.regions.iter().all(|region| matches!(region.kind(), ty::RePlaceholder(_))));
+    let distinct: FxHashSet<_> = after.regions.iter().collect();
+    assert_eq!(distinct.len(), after.regions.len());
+}
+
+/// Records the regions of a body that `RegionRefresher` rewrites, in visiting order.
+struct Regions<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    regions: Vec<ty::Region<'tcx>>,
+}
+
+impl<'tcx> Regions<'tcx> {
+    fn record<T: TypeFoldable<TyCtxt<'tcx>>>(&mut self, value: T) {
+        self.tcx.fold_regions(value, |region, _| {
+            if !region.is_bound() {
+                self.regions.push(region);
+            }
+            region
+        });
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Regions<'tcx> {
+    fn visit_ty(&mut self, ty: Ty<'tcx>, ty_context: TyContext) {
+        if !matches!(ty_context, TyContext::ReturnTy(_)) {
+            self.record(ty);
+        }
+    }
+
+    fn visit_args(&mut self, args: &GenericArgsRef<'tcx>, _location: Location) {
+        self.record(*args);
+    }
+
+    fn visit_region(&mut self, region: ty::Region<'tcx>, _location: Location) {
+        self.record(region);
+    }
+
+    fn visit_ty_const(&mut self, ct: ty::Const<'tcx>, _location: Location) {
+        self.record(ct);
+    }
+
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, _location: Location) {
+        self.record(constant.const_);
+    }
+}
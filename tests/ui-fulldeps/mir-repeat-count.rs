@@ -0,0 +1,71 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `RepeatCountSubstitutor` specializes the count of `[x; N]` to `[x; 3]` for
+// `N = 3`, and leaves it unchanged when `N` is only mapped to a parameter.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::repeat_count::RepeatCountSubstitutor;
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::{Body, Rvalue, StatementKind};
+use rustc_middle::ty::{self, GenericArgs, GenericArgsRef, TyCtxt};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn fill<const N: usize>(x: u8) -> [u8; N] {
+        [x; N]
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let fill = find_fn(tcx, Symbol::intern("fill"));
+
+        let three = ty::Const::from_target_usize(tcx, 3);
+        let count = repeat_count(tcx, fill, tcx.mk_args(&[three.into()]));
+        assert_eq!(count.try_to_target_usize(tcx), Some(3));
+
+        let identity = GenericArgs::identity_for_item(tcx, fill);
+        let count = repeat_count(tcx, fill, identity);
+        assert!(matches!(count.kind(), ty::ConstKind::Param(_)));
+    });
+}
+
+/// Instantiates the repeat counts of the optimized body of `def_id` with `args`, and returns
+/// the count of its only `Repeat`.
+fn repeat_count<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    args: GenericArgsRef<'tcx>,
+) -> ty::Const<'tcx> {
+    let mut body: Body<'tcx> = tcx.optimized_mir(def_id).clone();
+    RepeatCountSubstitutor::new(tcx, args).visit_body(&mut body);
+    let mut counts = Vec::new();
+    for statement in body.basic_blocks.iter().flat_map(|block| &block.statements) {
+        if let StatementKind::Assign(assign) = &statement.kind {
+            if let Rvalue::Repeat(_, count) = assign.1 {
+                counts.push(count);
+            }
+        }
+    }
+    assert_eq!(counts.len(), 1);
+    counts[0]
+}
@@ -0,0 +1,55 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `ReturnSlotUses` collects the assignment of the return place and the implicit
+// move out of it by the `Return` terminator.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::return_slot_uses::ReturnSlotUses;
+use rustc_middle::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext};
+use rustc_middle::mir::{Place, TerminatorKind};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn pair(a: u32, b: u32) -> (u32, u32) {
+        (a, b)
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let body = tcx.optimized_mir(find_fn(tcx, Symbol::intern("pair")));
+        let uses = ReturnSlotUses::collect(body);
+
+        let store = PlaceContext::MutatingUse(MutatingUseContext::Store);
+        let stores = uses
+            .iter()
+            .filter(|&&(place, context, _)| place == Place::return_place() && context == store);
+        assert_eq!(stores.count(), 1);
+
+        let (block, _) = body
+            .basic_blocks
+            .iter_enumerated()
+            .find(|(_, data)| matches!(data.terminator().kind, TerminatorKind::Return))
+            .unwrap();
+        let moved = PlaceContext::NonMutatingUse(NonMutatingUseContext::Move);
+        assert_eq!(uses.last(), Some(&(Place::return_place(), moved, body.terminator_loc(block))));
+    });
+}
@@ -0,0 +1,101 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `ScopedPlaceSubstitutor` only replaces a place in the blocks of its range, and
+// leaves the debuginfo of the replaced variable alone.
+
+#![feature(rustc_private)]
+
+extern crate rustc_data_structures;
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::place_substitution::ScopedPlaceSubstitutor;
+use rustc_middle::mir::visit::{MutVisitor, PlaceContext, Visitor};
+use rustc_middle::mir::{
+    BasicBlock, Body, Local, Location, Place, VarDebugInfo, VarDebugInfoContents, START_BLOCK,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn select(a: u32, b: u32, c: bool) -> u32 {
+        let x = a;
+        if c { x + a } else { b }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        check_substitution(tcx, find_fn(tcx, Symbol::intern("select")));
+    });
+}
+
+/// Replaces `a` with `b` in the first block of `def_id`, and checks that only the first block
+/// changed.
+fn check_substitution(tcx: TyCtxt<'_>, def_id: DefId) {
+    let mut body: Body<'_> = tcx.optimized_mir(def_id).clone();
+    let a = Local::from_u32(1);
+    let b = Local::from_u32(2);
+
+    let before = LocalUses::collect(&body);
+    assert!(before.count(START_BLOCK, a) > 0);
+    assert!(before.0.keys().any(|&(block, local)| block != START_BLOCK && local == a));
+
+    ScopedPlaceSubstitutor::new(tcx, Place::from(a), Place::from(b), START_BLOCK..=START_BLOCK)
+        .visit_body(&mut body);
+
+    let after = LocalUses::collect(&body);
+    assert_eq!(after.count(START_BLOCK, a), 0);
+    assert_eq!(
+        after.count(START_BLOCK, b),
+        before.count(START_BLOCK, a) + before.count(START_BLOCK, b)
+    );
+    for (&(block, local), &count) in &before.0 {
+        if block != START_BLOCK {
+            assert_eq!(after.count(block, local), count);
+        }
+    }
+
+    let debuginfo = body.var_debug_info.iter().find(|info| info.name.as_str() == "a").unwrap();
+    let VarDebugInfoContents::Place(place) = debuginfo.value else { panic!() };
+    assert_eq!(place.local, a);
+}
+
+/// Counts the uses of each local in each block, leaving out the debuginfo.
+struct LocalUses(FxHashMap<(BasicBlock, Local), usize>);
+
+impl LocalUses {
+    fn collect(body: &Body<'_>) -> LocalUses {
+        let mut uses = LocalUses(FxHashMap::default());
+        uses.visit_body(body);
+        uses
+    }
+
+    fn count(&self, block: BasicBlock, local: Local) -> usize {
+        self.0.get(&(block, local)).copied().unwrap_or(0)
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for LocalUses {
+    fn visit_local(&mut self, local: Local, _context: PlaceContext, location: Location) {
+        *self.0.entry((location.block, local)).or_default() += 1;
+    }
+
+    fn visit_var_debug_info(&mut self, _var_debug_info: &VarDebugInfo<'tcx>) {}
+}
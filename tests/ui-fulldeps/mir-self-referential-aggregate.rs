@@ -0,0 +1,84 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `SelfReferentialAggregate` reports an aggregate that moves its own destination,
+// but neither a functional record update nor an update through a temporary.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::self_referential_aggregate::SelfReferentialAggregate;
+use rustc_middle::mir::{BasicBlock, Location};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn nested(a: i32) -> (i32, i32) {
+        mir! {
+            {
+                RET = (a, 1_i32);
+                RET = (RET.1, RET.0);
+                Return()
+            }
+        }
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn record_update(a: i32) -> (i32, i32) {
+        mir! {
+            {
+                RET = (a, 1_i32);
+                RET = (2_i32, RET.1);
+                Return()
+            }
+        }
+    }
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn through_temporary(a: i32) -> (i32, i32) {
+        mir! {
+            let t: i32;
+            {
+                RET = (a, 1_i32);
+                t = RET.1;
+                RET = (t, 2_i32);
+                Return()
+            }
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let swap = Location { block: BasicBlock::from_u32(0), statement_index: 1 };
+        assert_eq!(collect(tcx, "nested"), [swap]);
+        assert!(collect(tcx, "record_update").is_empty());
+        assert!(collect(tcx, "through_temporary").is_empty());
+    });
+}
+
+/// Returns the self-referential aggregates of the built body of the function `name`.
+fn collect(tcx: TyCtxt<'_>, name: &str) -> Vec<Location> {
+    let def_id = find_fn(tcx, Symbol::intern(name)).expect_local();
+    SelfReferentialAggregate::collect(&tcx.mir_promoted(def_id).0.borrow())
+}
@@ -0,0 +1,64 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `SpanCollector` collects the spans of the statements, terminators, calls and
+// local declarations of a body, each with the kind of node it belongs to.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::span_collector::{SpanCollector, SpanOrigin};
+use rustc_middle::mir::TerminatorKind;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #[inline(never)]
+    pub fn callee(x: u32) -> u32 {
+        x
+    }
+
+    pub fn caller(a: u32) -> u32 {
+        let b = callee(a);
+        b ^ a
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let body = tcx.optimized_mir(find_fn(tcx, Symbol::intern("caller")));
+        let spans = SpanCollector::collect(body);
+
+        assert!(spans.contains(&(body.span, SpanOrigin::Other)));
+        let mut calls = 0;
+        for block in body.basic_blocks.iter() {
+            for statement in &block.statements {
+                assert!(spans.contains(&(statement.source_info.span, SpanOrigin::Statement)));
+            }
+            let terminator = block.terminator();
+            assert!(spans.contains(&(terminator.source_info.span, SpanOrigin::Terminator)));
+            if let TerminatorKind::Call { fn_span, .. } = terminator.kind {
+                assert!(spans.contains(&(fn_span, SpanOrigin::FnSpan)));
+                calls += 1;
+            }
+        }
+        for local_decl in &body.local_decls {
+            assert!(spans.contains(&(local_decl.source_info.span, SpanOrigin::LocalDecl)));
+        }
+        assert_eq!(calls, 1);
+    });
+}
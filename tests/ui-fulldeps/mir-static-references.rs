@@ -0,0 +1,63 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `StaticSelfReference` finds the statics referenced by the initializer of a
+// static, including the static itself.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::static_references::StaticSelfReference;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::run_compiler;
+
+fn main() {
+    let src = r#"
+    pub struct Node(pub &'static Node);
+
+    pub static SELF: Node = Node(&SELF);
+    pub static OTHER: Node = Node(&SELF);
+    pub static PLAIN: u32 = 1;
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let [this, other, plain] = ["SELF", "OTHER", "PLAIN"].map(|name| find_static(tcx, name));
+
+        assert!(StaticSelfReference::refers_to_itself(tcx, this));
+        assert!(!StaticSelfReference::refers_to_itself(tcx, other));
+        assert_eq!(
+            StaticSelfReference::collect(tcx, other).into_iter().collect::<Vec<_>>(),
+            [this]
+        );
+        assert!(StaticSelfReference::collect(tcx, plain).is_empty());
+    });
+}
+
+/// Returns the static of the local crate named `name`.
+fn find_static(tcx: TyCtxt<'_>, name: &str) -> DefId {
+    let name = Symbol::intern(name);
+    tcx.hir()
+        .body_owners()
+        .map(|def_id| def_id.to_def_id())
+        .find(|&def_id| {
+            matches!(tcx.def_kind(def_id), DefKind::Static { .. }) && tcx.item_name(def_id) == name
+        })
+        .unwrap()
+}
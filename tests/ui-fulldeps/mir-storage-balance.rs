@@ -0,0 +1,74 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `StorageBalance` reports a `StorageLive` without any reachable `StorageDead`, and
+// nothing in the MIR built for ordinary functions.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::storage_balance::StorageBalance;
+use rustc_middle::mir::{BasicBlock, Local, Location};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn leaked(a: i32) -> i32 {
+        mir! {
+            let x: i32;
+            let y: i32;
+            {
+                StorageLive(x);
+                StorageLive(y);
+                x = a;
+                y = x;
+                StorageDead(x);
+                RET = y;
+                Return()
+            }
+        }
+    }
+
+    pub fn ordinary(a: &[u32]) -> u32 {
+        let mut sum = 0;
+        for &x in a {
+            let y = x * 2;
+            sum += y;
+        }
+        sum
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let y = Location { block: BasicBlock::from_u32(0), statement_index: 1 };
+        assert_eq!(check(tcx, "leaked"), [(Local::from_u32(3), y)]);
+        assert!(check(tcx, "ordinary").is_empty());
+    });
+}
+
+/// Returns the unbalanced `StorageLive`s of the built body of the function `name`.
+fn check(tcx: TyCtxt<'_>, name: &str) -> Vec<(Local, Location)> {
+    let def_id = find_fn(tcx, Symbol::intern(name)).expect_local();
+    StorageBalance::check(&tcx.mir_promoted(def_id).0.borrow())
+}
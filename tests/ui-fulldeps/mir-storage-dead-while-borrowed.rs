@@ -0,0 +1,72 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `StorageDeadWhileBorrowed` reports a `StorageDead` followed by a read through a
+// copy of a reference to the local, and nothing in the MIR built for an ordinary function.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::storage_dead_while_borrowed::StorageDeadWhileBorrowed;
+use rustc_middle::mir::{BasicBlock, Local, Location};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(custom_mir, core_intrinsics)]
+    extern crate core;
+    use core::intrinsics::mir::*;
+
+    #[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+    pub fn dangling(a: i32) -> i32 {
+        mir! {
+            let x: i32;
+            let r: &i32;
+            let s: &i32;
+            {
+                StorageLive(x);
+                x = a;
+                r = &x;
+                s = r;
+                StorageDead(x);
+                RET = *s;
+                Return()
+            }
+        }
+    }
+
+    pub fn ordinary(a: i32) -> i32 {
+        let x = a;
+        let r = &x;
+        *r
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let dead = Location { block: BasicBlock::from_u32(0), statement_index: 4 };
+        assert_eq!(check(tcx, "dangling"), [(Local::from_u32(2), dead)]);
+        assert!(check(tcx, "ordinary").is_empty());
+    });
+}
+
+/// Returns the early `StorageDead`s of the built body of the function `name`.
+fn check(tcx: TyCtxt<'_>, name: &str) -> Vec<(Local, Location)> {
+    let def_id = find_fn(tcx, Symbol::intern(name)).expect_local();
+    StorageDeadWhileBorrowed::check(&tcx.mir_promoted(def_id).0.borrow())
+}
@@ -0,0 +1,71 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `SubstApplier` instantiates every generic parameter of a body, in the types of
+// its locals as well as in the generic arguments of its calls.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::instantiate::SubstApplier;
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::{Body, TerminatorKind, RETURN_PLACE};
+use rustc_middle::ty::{Ty, TyCtxt, TypeVisitableExt};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    fn wrap<T>(x: T) -> Option<T> {
+        Some(x)
+    }
+
+    pub fn pair<T: Copy>(x: T) -> (Option<T>, T) {
+        let y = x;
+        (wrap(y), x)
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let pair = find_fn(tcx, Symbol::intern("pair"));
+        let wrap = find_fn(tcx, Symbol::intern("wrap"));
+
+        let mut body: Body<'_> = tcx.optimized_mir(pair).clone();
+        assert!(body.local_decls.iter().any(|decl| decl.ty.has_param()));
+
+        let args = tcx.mk_args(&[tcx.types.u32.into()]);
+        SubstApplier { tcx, args }.visit_body(&mut body);
+
+        for decl in body.local_decls.iter() {
+            assert!(!decl.ty.has_param(), "{:?} was not instantiated", decl.ty);
+        }
+        let option_u32 = tcx.fn_sig(wrap).instantiate(tcx, args).output().skip_binder();
+        let expected = Ty::new_tup(tcx, &[option_u32, tcx.types.u32]);
+        assert_eq!(body.local_decls[RETURN_PLACE].ty, expected);
+
+        let mut calls = 0;
+        for block in body.basic_blocks.iter() {
+            if let TerminatorKind::Call { func, .. } = &block.terminator().kind {
+                let (callee, callee_args) = func.const_fn_def().unwrap();
+                assert_eq!(callee, wrap);
+                assert_eq!(callee_args, args);
+                calls += 1;
+            }
+        }
+        assert_eq!(calls, 1);
+    });
+}
@@ -0,0 +1,65 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `SwitchCoverageChecker` accepts the switch of an exhaustive match on an enum, and
+// reports it once the arm of a variant is removed while the otherwise target stays unreachable.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::switch_coverage::SwitchCoverageChecker;
+use rustc_middle::mir::{Body, SwitchTargets, TerminatorKind};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub enum Abc {
+        A,
+        B,
+        C,
+    }
+
+    pub fn exhaustive(abc: Abc) -> u32 {
+        match abc {
+            Abc::A => 1,
+            Abc::B => 2,
+            Abc::C => 3,
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let exhaustive = find_fn(tcx, Symbol::intern("exhaustive"));
+        let param_env = tcx.param_env(exhaustive);
+
+        // The body as built, where the otherwise target of the switch is unreachable.
+        let mut body: Body<'_> = tcx.mir_promoted(exhaustive.expect_local()).0.borrow().clone();
+        assert!(SwitchCoverageChecker::check(tcx, param_env, &body).is_empty());
+
+        let mut switches = Vec::new();
+        for (block, data) in body.basic_blocks_mut().iter_enumerated_mut() {
+            if let TerminatorKind::SwitchInt { targets, .. } = &mut data.terminator_mut().kind {
+                let arms: Vec<_> = targets.iter().filter(|&(value, _)| value != 2).collect();
+                *targets = SwitchTargets::new(arms.into_iter(), targets.otherwise());
+                switches.push(block);
+            }
+        }
+        assert_eq!(switches.len(), 1);
+        assert_eq!(SwitchCoverageChecker::check(tcx, param_env, &body), switches);
+    });
+}
@@ -0,0 +1,58 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `TypesByLocation` groups the types of a body by local and by location.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::types_by_location::TypesByLocation;
+use rustc_middle::mir::{Local, Location, Rvalue, StatementKind, RETURN_PLACE};
+use rustc_span::Symbol;
+
+use driver::{find_fn, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn widen(a: u32) -> u64 {
+        a as u64
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let body = tcx.optimized_mir(find_fn(tcx, Symbol::intern("widen")));
+        let types = TypesByLocation::collect(body);
+        let (u32, u64) = (tcx.types.u32, tcx.types.u64);
+
+        // The return type is recorded for the return place, along with its declared type.
+        assert_eq!(types.by_local[&RETURN_PLACE], [u64, u64]);
+        assert_eq!(types.by_local[&Local::from_u32(1)], [u32]);
+        assert!(types.other.is_empty());
+
+        // The target type of the cast is recorded at the location of the cast.
+        let (block, data) = body.basic_blocks.iter_enumerated().next().unwrap();
+        let statement_index = data
+            .statements
+            .iter()
+            .position(|statement| match &statement.kind {
+                StatementKind::Assign(assign) => matches!(assign.1, Rvalue::Cast(..)),
+                _ => false,
+            })
+            .unwrap();
+        let cast = Location { block, statement_index };
+        assert!(types.by_location[&cast].contains(&u64));
+    });
+}
@@ -0,0 +1,48 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `UnusedLocals` finds a variable that is declared but never used, and neither a
+// used variable nor an unused argument.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_middle::mir::unused_locals::UnusedLocals;
+use rustc_middle::mir::Local;
+use rustc_span::Symbol;
+
+use driver::{find_fn, local_named, run_compiler};
+
+fn main() {
+    let src = r#"
+    pub fn locals(a: u32, b: u32) -> u32 {
+        let declared: u32;
+        let used = a;
+        used
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let locals = find_fn(tcx, Symbol::intern("locals")).expect_local();
+
+        // The body as built, before the unused locals are removed.
+        let body = tcx.mir_promoted(locals).0.borrow();
+        let unused = UnusedLocals::compute(&body);
+        assert!(unused.contains(&local_named(&body, "declared")));
+        assert!(!unused.contains(&local_named(&body, "used")));
+        assert!(!unused.contains(&Local::from_u32(2)));
+    });
+}
@@ -0,0 +1,85 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: {{sysroot-base}}
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Checks that `YieldBorrowChecker` reports a borrow whose reference is used on both sides of a
+// yield of a movable coroutine, and never reports anything in a static coroutine.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_span;
+
+#[path = "auxiliary/mir-driver.rs"]
+mod driver;
+
+use rustc_hir::Movability;
+use rustc_middle::mir::yield_borrows::YieldBorrowChecker;
+use rustc_middle::mir::{Rvalue, StatementKind};
+
+use driver::{local_named, run_compiler};
+
+fn main() {
+    let src = r#"
+    #![feature(coroutines, coroutine_trait, stmt_expr_attributes)]
+
+    use std::ops::Coroutine;
+
+    pub fn movable() -> impl Coroutine<Yield = u32, Return = ()> {
+        #[coroutine]
+        || {
+            let v = 7;
+            loop {
+                let r = &v;
+                yield *r;
+            }
+        }
+    }
+
+    pub fn fixed() -> impl Coroutine<Yield = u32, Return = ()> {
+        #[coroutine]
+        static || {
+            let v = 7;
+            loop {
+                let r = &v;
+                yield *r;
+            }
+        }
+    }
+
+    fn main() {}
+    "#;
+
+    run_compiler(src, |tcx| {
+        let coroutines =
+            tcx.hir().body_owners().filter(|&def_id| tcx.is_coroutine(def_id.to_def_id()));
+        for coroutine in coroutines {
+            // The body before the coroutine transform, which still has the `Yield` terminators.
+            let body = tcx.mir_promoted(coroutine).0.borrow();
+            let reported = YieldBorrowChecker::check(&body);
+            if body.coroutine_kind().unwrap().movability() == Movability::Static {
+                assert!(reported.is_empty());
+                continue;
+            }
+
+            // `r` is reassigned after resuming, which the check over-approximates as a use
+            // across the yield.
+            let v = local_named(&body, "v");
+            assert!(reported.iter().any(|&location| {
+                let statement = body.stmt_at(location).left().unwrap();
+                match &statement.kind {
+                    StatementKind::Assign(assign) => match &assign.1 {
+                        Rvalue::Ref(_, _, borrowed) => borrowed.local == v,
+                        _ => false,
+                    },
+                    _ => false,
+                }
+            }));
+        }
+    });
+}
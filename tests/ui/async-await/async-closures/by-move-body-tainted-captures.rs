@@ -0,0 +1,18 @@
+//@ edition: 2021
+
+#![feature(async_closure)]
+
+// Don't ICE when building the by-move body of an `FnOnce` async closure whose captures
+// disagree with the captures of its coroutine because of an earlier error.
+
+fn consume<T>(_: T) {}
+
+fn main() {
+    let s = String::new();
+    let t = String::new();
+    let _ = async move || {
+        consume(s);
+        let _: () = t.missing_method();
+        //~^ ERROR no method named `missing_method` found for struct `String` in the current scope
+    };
+}
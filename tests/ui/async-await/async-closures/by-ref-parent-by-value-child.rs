@@ -0,0 +1,26 @@
+//@ aux-build:block-on.rs
+//@ edition:2021
+//@ run-pass
+
+// Check that the by-move body adds a deref when the parent coroutine-closure captures an
+// upvar by ref but the inner coroutine consumes it by value.
+
+#![feature(async_closure)]
+
+extern crate block_on;
+
+async fn call_once(f: impl async FnOnce()) {
+    f().await
+}
+
+fn main() {
+    block_on::block_on(async {
+        let x = 1i32;
+        let r = &x;
+        let c = async || {
+            let r: &i32 = { r };
+            assert_eq!(*r, 1);
+        };
+        call_once(c).await;
+    });
+}
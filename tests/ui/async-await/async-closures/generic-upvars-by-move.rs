@@ -0,0 +1,38 @@
+//@ aux-build:block-on.rs
+//@ edition:2021
+//@ run-pass
+
+// Check that the by-move body of an async closure that is generic, and whose captures
+// have types that mention projections, can be built and called.
+
+#![feature(async_closure)]
+
+extern crate block_on;
+
+trait Assoc {
+    type Ty: Clone + std::fmt::Debug + PartialEq;
+    fn value(&self) -> Self::Ty;
+}
+
+impl Assoc for u32 {
+    type Ty = String;
+    fn value(&self) -> String {
+        self.to_string()
+    }
+}
+
+async fn call_once(f: impl async FnOnce()) {
+    f().await
+}
+
+async fn generic<T: Assoc>(t: T, expected: T::Ty) {
+    let value: T::Ty = t.value();
+    let c = async move || {
+        assert_eq!(value, expected);
+    };
+    call_once(c).await;
+}
+
+fn main() {
+    block_on::block_on(generic(7u32, String::from("7")));
+}
@@ -0,0 +1,44 @@
+//@ aux-build:block-on.rs
+//@ edition:2021
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir
+
+// Check that the by-move body of a coroutine-closure that only captures zero-sized values,
+// which needs no place to be rewritten, is still well-formed and behaves like the by-ref one.
+
+#![feature(async_closure)]
+
+extern crate block_on;
+
+#[derive(Debug, PartialEq)]
+struct Unit;
+
+fn main() {
+    block_on::block_on(async_main());
+}
+
+async fn call<T>(f: &impl async Fn() -> T) -> T {
+    f().await
+}
+
+async fn call_once<T>(f: impl async FnOnce() -> T) -> T {
+    f().await
+}
+
+async fn async_main() {
+    // Capture a unit struct and a unit by value, and move out of them.
+    let unit = Unit;
+    let empty = ();
+    let c = async move || (unit, empty);
+    assert_eq!(call_once(c).await, (Unit, ()));
+
+    // Capture zero-sized values by value and only read them.
+    let unit = Unit;
+    let array: [u32; 0] = [];
+    let c = async move || {
+        assert_eq!(unit, Unit);
+        array.len()
+    };
+    assert_eq!(call(&c).await, 0);
+    assert_eq!(call_once(c).await, 0);
+}
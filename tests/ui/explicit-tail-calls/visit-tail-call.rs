@@ -0,0 +1,19 @@
+//@ check-pass
+//@ compile-flags: -Zvalidate-mir
+#![allow(incomplete_features)]
+#![feature(explicit_tail_calls)]
+
+// Checks that the MIR visitors handle the callee and arguments of `TailCall` terminators.
+
+fn f(x: u32, y: String) -> usize {
+    x as usize + y.len()
+}
+
+fn g(x: u32) -> usize {
+    let y = String::from("tail");
+    become f(x, y);
+}
+
+fn main() {
+    let _ = g(1);
+}
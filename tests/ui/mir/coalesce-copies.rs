@@ -0,0 +1,38 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+CopyCoalescer -Copt-level=0
+
+// Checks that making the copies of a chain of copied locals copy the start of the chain keeps
+// the values, including when the start of the chain is moved or goes out of scope.
+
+use std::hint::black_box;
+
+fn straight_line(x: u32) -> u32 {
+    let a = x + 1;
+    let b = a;
+    let c = b;
+    let d = c;
+    b + c + d
+}
+
+fn out_of_scope(x: u64) -> u64 {
+    let c;
+    {
+        let a = x * 2;
+        let b = a;
+        c = b;
+    }
+    c + c
+}
+
+fn moved_start(v: Vec<u8>) -> (usize, usize) {
+    let len = v.len();
+    let copy = len;
+    drop(v);
+    (len, copy)
+}
+
+fn main() {
+    assert_eq!(straight_line(black_box(1)), 6);
+    assert_eq!(out_of_scope(black_box(3)), 12);
+    assert_eq!(moved_start(black_box(vec![1, 2, 3])), (3, 3));
+}
@@ -0,0 +1,61 @@
+//@ run-pass
+//@ needs-unwind
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+GotoChainCollapser -Copt-level=0
+
+// Checks that bypassing the blocks that only contain a `Goto` keeps the CFG valid, including
+// for the chains of cleanup blocks, and keeps empty infinite loops.
+
+use std::hint::black_box;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+struct Noisy<'a>(&'a mut u32);
+
+impl Drop for Noisy<'_> {
+    fn drop(&mut self) {
+        *self.0 += 1;
+    }
+}
+
+fn nested_branches(x: u32) -> u32 {
+    let mut total = 0;
+    for i in 0..x {
+        if i % 2 == 0 {
+            if i % 3 == 0 {
+                total += 1;
+            }
+        } else {
+            loop {
+                if black_box(true) {
+                    break;
+                }
+            }
+        }
+    }
+    total
+}
+
+fn drops_on_unwind(drops: &mut u32, fail: bool) {
+    let _a = Noisy(drops);
+    if fail {
+        panic!("unwinding through the cleanup blocks");
+    }
+}
+
+fn spin() {
+    loop {}
+}
+
+fn main() {
+    assert_eq!(nested_branches(black_box(10)), 2);
+
+    let mut drops = 0;
+    drops_on_unwind(&mut drops, false);
+    assert_eq!(drops, 1);
+    let result = catch_unwind(AssertUnwindSafe(|| drops_on_unwind(&mut drops, true)));
+    assert!(result.is_err());
+    assert_eq!(drops, 2);
+
+    if black_box(false) {
+        spin();
+    }
+}
@@ -0,0 +1,17 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+DeduplicateConsts -Copt-level=0
+
+// Checks that sharing equal constants keeps their values, including for equal bytes at
+// different types.
+
+#[inline(never)]
+fn arrays() -> ([u8; 4], [u8; 4], [i8; 4]) {
+    ([1, 2, 3, 250], [1, 2, 3, 250], [1, 2, 3, -6])
+}
+
+fn main() {
+    let (a, b, c) = arrays();
+    assert_eq!(a, [1, 2, 3, 250]);
+    assert_eq!(a, b);
+    assert_eq!(c, [1, 2, 3, -6]);
+}
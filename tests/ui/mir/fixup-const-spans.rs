@@ -0,0 +1,22 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+FixupConstSpans -Copt-level=0
+
+// Checks that giving constants the span of the statement or terminator using them doesn't
+// change their values, whether they are used by statements, calls or assertions.
+
+const ARRAY: [u32; 3] = [1, 2, 3];
+
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+fn main() {
+    let x = add(40, 2);
+    assert_eq!(x, 42);
+
+    let i = 2;
+    assert_eq!(ARRAY[i], 3);
+
+    let s = "constant";
+    assert_eq!(s.len(), 8);
+}
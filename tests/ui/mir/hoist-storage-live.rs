@@ -0,0 +1,18 @@
+//@ build-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+StorageLiveHoister -Copt-level=0
+
+// Checks that moving `StorageLive` markers to the start of their block produces valid MIR,
+// including when a local is killed and revived within the same block.
+
+fn consume(_: String) {}
+
+fn main() {
+    let mut i = 0;
+    while i < 3 {
+        let a = String::from("a");
+        consume(a);
+        let b = String::from("b");
+        consume(b);
+        i += 1;
+    }
+}
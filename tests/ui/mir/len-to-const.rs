@@ -0,0 +1,29 @@
+//@ run-pass
+//@ needs-unwind
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+ConstArrayLen -Copt-level=0
+
+// Checks that the length of an array can be replaced with a constant, including when it is a
+// const parameter, while the length of a slice is left to be computed at runtime.
+
+fn array_index(a: [u8; 4], i: usize) -> u8 {
+    a[i]
+}
+
+fn generic_array_index<const N: usize>(a: [u8; N], i: usize) -> u8 {
+    a[i]
+}
+
+fn slice_index(s: &[u8], i: usize) -> u8 {
+    s[i]
+}
+
+fn main() {
+    assert_eq!(array_index([1, 2, 3, 4], 3), 4);
+    assert_eq!(generic_array_index([5, 6], 1), 6);
+    assert_eq!(slice_index(&[7, 8, 9], 2), 9);
+
+    let result = std::panic::catch_unwind(|| array_index([1, 2, 3, 4], 4));
+    assert!(result.is_err());
+    let result = std::panic::catch_unwind(|| slice_index(&[7, 8, 9], 3));
+    assert!(result.is_err());
+}
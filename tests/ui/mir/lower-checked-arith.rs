@@ -0,0 +1,74 @@
+//@ run-pass
+//@ needs-unwind
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+LowerCheckedArith -Copt-level=0
+//@ compile-flags: -Coverflow-checks=on
+
+// Checks that splitting checked additions and subtractions into the wrapping operation and an
+// explicit overflow flag computes the same values, and that the overflow `Assert` still panics
+// and unwinds through the cleanup of the function.
+
+use std::cell::Cell;
+use std::hint::black_box;
+use std::panic::catch_unwind;
+
+fn add<T: std::ops::Add<Output = T>>(a: T, b: T) -> T {
+    a + b
+}
+
+fn add_u8(a: u8, b: u8) -> u8 {
+    a + b
+}
+
+fn add_i8(a: i8, b: i8) -> i8 {
+    a + b
+}
+
+fn sub_u8(a: u8, b: u8) -> u8 {
+    a - b
+}
+
+fn sub_i8(a: i8, b: i8) -> i8 {
+    a - b
+}
+
+struct Noisy<'a>(&'a Cell<u32>);
+
+impl Drop for Noisy<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn add_with_live_drop(drops: &Cell<u32>, a: u8, b: u8) -> u8 {
+    let _noisy = Noisy(drops);
+    a + b
+}
+
+fn overflows<F: FnOnce() -> R + std::panic::UnwindSafe, R>(f: F) -> bool {
+    catch_unwind(f).is_err()
+}
+
+fn main() {
+    assert_eq!(add_u8(black_box(200), black_box(55)), 255);
+    assert_eq!(add_i8(black_box(-100), black_box(-28)), -128);
+    assert_eq!(add_i8(black_box(100), black_box(27)), 127);
+    assert_eq!(sub_u8(black_box(5), black_box(5)), 0);
+    assert_eq!(sub_i8(black_box(-100), black_box(28)), -128);
+    assert_eq!(sub_i8(black_box(100), black_box(-27)), 127);
+    assert_eq!(add(black_box(1u64), black_box(2)), 3);
+
+    assert!(overflows(|| add_u8(black_box(200), black_box(56))));
+    assert!(overflows(|| add_i8(black_box(-100), black_box(-29))));
+    assert!(overflows(|| add_i8(black_box(100), black_box(28))));
+    assert!(overflows(|| sub_u8(black_box(5), black_box(6))));
+    assert!(overflows(|| sub_i8(black_box(-100), black_box(29))));
+    assert!(overflows(|| sub_i8(black_box(100), black_box(-28))));
+    assert!(overflows(|| add(black_box(u64::MAX), black_box(1))));
+
+    let drops = Cell::new(0);
+    assert_eq!(add_with_live_drop(&drops, black_box(1), black_box(2)), 3);
+    assert_eq!(drops.get(), 1);
+    let drops = std::panic::AssertUnwindSafe(Cell::new(0));
+    assert!(overflows(|| add_with_live_drop(&drops, black_box(255), black_box(1))));
+    assert_eq!(drops.get(), 1);
+}
@@ -0,0 +1,58 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+LowerTrivialDiscriminants -Zinline-mir
+//@ compile-flags: -Copt-level=0
+
+// Checks that replacing the discriminant reads of single-variant enums and structs with a
+// constant gives the same values as reading them, and leaves the other enums alone.
+
+#![feature(core_intrinsics, discriminant_kind)]
+#![allow(internal_features)]
+
+use std::hint::black_box;
+use std::intrinsics::discriminant_value;
+use std::mem::discriminant;
+
+#[derive(Clone, Copy)]
+enum Single {
+    A,
+}
+
+#[derive(Clone, Copy)]
+#[repr(i16)]
+enum Explicit {
+    A = -3,
+}
+
+#[derive(Clone, Copy)]
+enum Generic<T> {
+    A(T),
+}
+
+#[derive(Clone, Copy)]
+enum Multi {
+    A = 4,
+    B,
+}
+
+#[derive(Clone, Copy)]
+struct Struct(u8);
+
+#[inline(always)]
+fn generic_discriminant<T>(x: &T) -> <T as std::marker::DiscriminantKind>::Discriminant {
+    discriminant_value(x)
+}
+
+fn main() {
+    assert_eq!(discriminant_value(&black_box(Single::A)), 0isize);
+    assert_eq!(discriminant_value(&black_box(Explicit::A)), -3i16);
+    assert_eq!(discriminant_value(&black_box(Generic::A(1u32))), 0isize);
+    assert_eq!(discriminant_value(&black_box(Struct(9))), 0u8);
+    assert_eq!(generic_discriminant(&black_box(Explicit::A)), -3i16);
+    assert_eq!(generic_discriminant(&black_box(Generic::A("a"))), 0isize);
+
+    assert_eq!(discriminant_value(&black_box(Multi::A)), 4isize);
+    assert_eq!(discriminant_value(&black_box(Multi::B)), 5isize);
+    assert_eq!(generic_discriminant(&black_box(Multi::B)), 5isize);
+    assert_eq!(discriminant(&black_box(Single::A)), discriminant(&Single::A));
+    assert_ne!(discriminant(&black_box(Multi::A)), discriminant(&Multi::B));
+}
@@ -0,0 +1,43 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+LowerZeroRepeats -Zinline-mir
+//@ compile-flags: -Copt-level=0
+
+// Checks that replacing the repeats whose count becomes zero once inlined with an empty array
+// keeps the other repeats and the drops of the repeated values as they were.
+
+use std::cell::Cell;
+use std::hint::black_box;
+
+struct Noisy<'a>(&'a Cell<u32>);
+
+impl Drop for Noisy<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+const EMPTY: Option<Box<u8>> = None;
+
+#[inline(always)]
+fn repeat<const N: usize>(x: u8) -> [u8; N] {
+    [x; N]
+}
+
+#[inline(always)]
+fn repeat_const<const N: usize>() -> [Option<Box<u8>>; N] {
+    [EMPTY; N]
+}
+
+fn main() {
+    assert_eq!(repeat::<0>(black_box(7)), []);
+    assert_eq!(repeat::<3>(black_box(7)), [7, 7, 7]);
+    assert_eq!(repeat_const::<0>().len(), 0);
+    assert_eq!(repeat_const::<2>(), [None, None]);
+
+    let drops = Cell::new(0);
+    let noisy = Noisy(&drops);
+    let empty: [Noisy<'_>; 0] = [noisy; 0];
+    assert_eq!(drops.get(), 1);
+    drop(empty);
+    assert_eq!(drops.get(), 1);
+}
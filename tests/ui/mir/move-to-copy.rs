@@ -0,0 +1,28 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+ReplaceCopyMoves -Copt-level=0
+
+// Checks that moves of `Copy` values can be turned into copies, while moves of non-`Copy`
+// values and of values whose type depends on generic parameters are left alone.
+
+fn take_i32(x: i32) -> i32 {
+    x + 1
+}
+
+fn take_vec(v: Vec<i32>) -> usize {
+    v.len()
+}
+
+fn take_generic<T: Copy>(t: T) -> T {
+    t
+}
+
+fn main() {
+    let x = 41;
+    assert_eq!(take_i32(x), 42);
+    assert_eq!(x, 41);
+
+    let v = vec![1, 2, 3];
+    assert_eq!(take_vec(v), 3);
+
+    assert_eq!(take_generic(7u8), 7);
+}
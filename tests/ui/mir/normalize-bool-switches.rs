@@ -0,0 +1,38 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+NormalizeBoolSwitches -Copt-level=0
+
+// Checks that rewriting the switches on booleans to test for `true` keeps both branches going
+// to the same place.
+
+fn if_else(b: bool) -> u8 {
+    if b { 1 } else { 2 }
+}
+
+fn match_bool(b: bool) -> u8 {
+    match b {
+        false => 3,
+        true => 4,
+    }
+}
+
+fn negated(b: bool) -> u8 {
+    if !b { 5 } else { 6 }
+}
+
+fn integer(x: u8) -> u8 {
+    match x {
+        0 => 7,
+        _ => 8,
+    }
+}
+
+fn main() {
+    assert_eq!(if_else(true), 1);
+    assert_eq!(if_else(false), 2);
+    assert_eq!(match_bool(false), 3);
+    assert_eq!(match_bool(true), 4);
+    assert_eq!(negated(false), 5);
+    assert_eq!(negated(true), 6);
+    assert_eq!(integer(0), 7);
+    assert_eq!(integer(1), 8);
+}
@@ -0,0 +1,26 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+NormalizeCommutativeOps -Copt-level=0
+
+// Checks that reordering the operands of commutative operations preserves the results, and
+// that non-commutative operations like subtraction are left alone.
+
+#[inline(never)]
+fn add(a: i32, b: i32) -> (i32, i32) {
+    (a + b, b + a)
+}
+
+#[inline(never)]
+fn sub(a: i32, b: i32) -> (i32, i32) {
+    (a - b, b - a)
+}
+
+#[inline(never)]
+fn add_const(a: u8) -> (u8, u8) {
+    (a.wrapping_add(3), 3u8.wrapping_add(a))
+}
+
+fn main() {
+    assert_eq!(add(2, 5), (7, 7));
+    assert_eq!(sub(2, 5), (-3, 3));
+    assert_eq!(add_const(4), (7, 7));
+}
@@ -0,0 +1,27 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+RemoveBoundsChecks -Copt-level=0
+
+// Checks that removing the bounds checks of indexing operations leaves a consistent CFG, both
+// when the removed check had a cleanup edge to drop a local and when it didn't.
+
+struct Noisy(u32);
+
+impl Drop for Noisy {
+    fn drop(&mut self) {}
+}
+
+fn sum(values: &[u32], indices: &[usize]) -> u32 {
+    let _guard = Noisy(0);
+    let mut total = 0;
+    for &i in indices {
+        total += values[i];
+    }
+    total
+}
+
+fn main() {
+    let array = [1, 2, 3, 4];
+    let i = std::hint::black_box(2);
+    assert_eq!(array[i], 3);
+    assert_eq!(sum(&array, &[0, 3]), 5);
+}
@@ -0,0 +1,18 @@
+//@ build-pass
+//@ compile-flags: -Zvalidate-mir -Zinline-mir -Zmir-opt-level=4
+
+// Checks that `Subtype` projections survive the MIR visitors used by the inliner and the
+// other optimizations: their type is visited like the one of `Field` and `OpaqueCast`
+// projections, and the projection itself is preserved.
+
+fn higher_ranked(_: &()) {}
+
+#[inline(always)]
+fn coerce(f: for<'a> fn(&'a ())) -> fn(&'static ()) {
+    f
+}
+
+fn main() {
+    let f = coerce(higher_ranked);
+    f(&());
+}
@@ -0,0 +1,36 @@
+//@ run-pass
+//@ compile-flags: -Zvalidate-mir -Zmir-enable-passes=+UnreachableToAbort -Copt-level=0
+
+// Checks that replacing `Unreachable` terminators with calls to `abort` produces valid MIR,
+// also in bodies with cleanup blocks, and that the reachable code still runs as before.
+
+enum Never {}
+
+struct Noisy;
+
+impl Drop for Noisy {
+    fn drop(&mut self) {}
+}
+
+fn get(x: Result<u32, Never>) -> u32 {
+    let _noisy = Noisy;
+    match x {
+        Ok(x) => x,
+        Err(never) => match never {},
+    }
+}
+
+fn pick(i: u8) -> &'static str {
+    let _noisy = Noisy;
+    match i % 3 {
+        0 => "zero",
+        1 => "one",
+        2 => "two",
+        _ => unsafe { std::hint::unreachable_unchecked() },
+    }
+}
+
+fn main() {
+    assert_eq!(get(Ok(3)), 3);
+    assert_eq!(pick(4), "one");
+}